@@ -9,12 +9,13 @@
 //!
 //! This is accomplished by storing the character offsets of each character in the string,
 //! along with the original [`String`], and using this information to calculate the byte
-//! offsets of each character on the fly. Thus [`IndexedString`] uses ~2x the memory of a
-//! normal [`String`], but [`IndexedSlice`] and other types implementing [`IndexedStr`] have
-//! only one [`usize`] extra in overhead over that of a regular [`&str`](`str`) slice / fat
-//! pointer. In theory this could be reduced down to the same size as a fat pointer using
-//! unsafe rust, but this way we get to have completely safe code and the difference is
-//! negligible.
+//! offsets of each character on the fly. Characters themselves are decoded on demand from
+//! these offsets, so beyond the [`String`] itself, [`IndexedString`] only stores one offset per
+//! character (a [`u32`] for strings under 4 GiB). [`IndexedSlice`] and other types
+//! implementing [`IndexedStr`] have only one [`usize`] extra in overhead over that of a
+//! regular [`&str`](`str`) slice / fat pointer. In theory this could be reduced down to the
+//! same size as a fat pointer using unsafe rust, but this way we get to have completely safe
+//! code and the difference is negligible.
 //! # Examples
 //!
 //! ```
@@ -54,11 +55,35 @@
 //! let standard_str_slice = slice.as_str();
 //! assert_eq!(standard_str_slice, "Hello");
 //! ```
-
+//!
+//! # Features
+//!
+//! - `std` (enabled by default): implements `std::error::Error` for [`ConsistencyError`].
+//!   Without it the crate is `no_std` and only requires [`alloc`].
+//! - `serde`: implements `Serialize` and `Deserialize` for [`IndexedString`] (and `Serialize`
+//!   for [`IndexedSlice`] and [`OwnedIndexedSlice`]), represented as a plain string.
+//! - `unicode`: enables grapheme-cluster-aware methods such as `grapheme_chunks`, the
+//!   grapheme-indexed `IndexedGraphemeString` type, and `is_identifier`.
+//! - `width`: makes column calculations such as [`expand_tabs`](`IndexedStr::expand_tabs`)
+//!   account for the display width of wide (e.g. CJK) and zero-width characters.
+
+#![no_std]
 #![deny(missing_docs)]
 
+extern crate alloc;
+#[cfg(feature = "std")]
+extern crate std;
+
+use alloc::borrow::Cow;
+use alloc::boxed::Box;
+use alloc::collections::{BTreeMap, BTreeSet};
+use alloc::string::{String, ToString};
+#[cfg(target_has_atomic = "ptr")]
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::cmp::Ordering;
 use core::fmt::{Debug, Display};
-use core::ops::{Bound, RangeBounds};
+use core::ops::{Bound, Deref, Index, Range, RangeBounds};
 use core::str::FromStr;
 
 /// A trait that facilitates safe interaction with strings that contain multi-byte characters.
@@ -87,7 +112,7 @@ pub trait IndexedStr:
     fn as_str(&self) -> &str;
 
     /// Returns a [`IndexedSlice`] that represents the entire contents of this [`IndexedStr`].
-    fn as_slice(&self) -> IndexedSlice;
+    fn as_slice(&self) -> IndexedSlice<'_>;
 
     /// Returns the length of this [`IndexedStr`] in characters, NOT bytes.
     fn len(&self) -> usize;
@@ -104,14 +129,225 @@ pub trait IndexedStr:
     /// Returns the character at the given index, if it exists.
     fn char_at(&self, index: usize) -> Option<char>;
 
+    /// Returns the character at the given index, wrapping around to the start of the string
+    /// when `index` is out of bounds (i.e. the character at `index % len()`).
+    ///
+    /// Returns `None` only if this [`IndexedStr`] is empty.
+    fn char_at_wrapping(&self, index: usize) -> Option<char> {
+        if self.is_empty() {
+            return None;
+        }
+        self.char_at(index % self.len())
+    }
+
+    /// Returns the character index of the character starting at the given byte offset, or
+    /// `None` if `byte` does not fall on a character boundary or is out of range.
+    ///
+    /// A `byte` equal to [`byte_len`](`IndexedStr::byte_len`) maps to
+    /// [`len`](`IndexedStr::len`). For an [`IndexedSlice`], both the byte offset and the
+    /// returned index are relative to the start of the slice.
+    fn char_index_for_byte(&self, byte: usize) -> Option<usize> {
+        let slice = self.as_slice();
+        match byte.cmp(&slice.byte_len()) {
+            Ordering::Equal => return Some(slice.len()),
+            Ordering::Greater => return None,
+            Ordering::Less => {}
+        }
+        let target = slice.source.byte_offset(slice.start) + byte;
+        slice.source.offsets.search(slice.start, slice.end, target)
+    }
+
+    /// Returns the character index and the character whose bytes contain the given byte
+    /// offset, or `None` if this [`IndexedStr`] is empty.
+    ///
+    /// Unlike [`char_index_for_byte`](`IndexedStr::char_index_for_byte`), `byte` may land
+    /// inside a multi-byte character, and an out-of-range `byte` is clamped to the last
+    /// character. For an [`IndexedSlice`], both the byte offset and the returned index are
+    /// relative to the start of the slice.
+    fn char_containing_byte(&self, byte: usize) -> Option<(usize, char)> {
+        let slice = self.as_slice();
+        if slice.is_empty() {
+            return None;
+        }
+        let byte = byte.min(slice.byte_len() - 1);
+        let target = slice.source.byte_offset(slice.start) + byte;
+        let index = slice.source.offsets.floor(slice.start, slice.end, target);
+        Some((index, slice.char_at(index)?))
+    }
+
+    /// Returns the byte offset of the character at the given character index, or `None` if
+    /// `char_index` is out of range.
+    ///
+    /// A `char_index` equal to [`len`](`IndexedStr::len`) maps to
+    /// [`byte_len`](`IndexedStr::byte_len`). For an [`IndexedSlice`], both the index and the
+    /// returned byte offset are relative to the start of the slice.
+    fn byte_index_for_char(&self, char_index: usize) -> Option<usize> {
+        let slice = self.as_slice();
+        if char_index > slice.len() {
+            return None;
+        }
+        let start = slice.source.byte_offset(slice.start);
+        Some(slice.source.byte_offset(slice.start + char_index) - start)
+    }
+
     /// Returns a sub-slice of this [`IndexedStr`] based on the given range in terms of the
     /// _characters_ in the string, not bytes.
     ///
     /// The range is automatically clamped to the bounds of the [`IndexedStr`].
-    fn slice<R: RangeBounds<usize>>(&self, range: R) -> IndexedSlice;
+    fn slice<R: RangeBounds<usize>>(&self, range: R) -> IndexedSlice<'_>;
+
+    /// Returns a sub-slice of this [`IndexedStr`] based on the given range in terms of
+    /// _bytes_ rather than characters.
+    ///
+    /// The range is clamped to [`byte_len`](`IndexedStr::byte_len`) and then widened to the
+    /// nearest character boundaries: a start that lands inside a multi-byte character snaps
+    /// down to the start of that character, and an end that lands inside one snaps up to its
+    /// end, so any partially covered character is included whole. An empty or reversed range
+    /// yields an empty slice positioned at the snapped-down start.
+    fn byte_slice<R: RangeBounds<usize>>(&self, range: R) -> IndexedSlice<'_> {
+        let slice = self.as_slice();
+        let byte_len = slice.byte_len();
+        let start = match range.start_bound() {
+            Bound::Included(&start) => start,
+            Bound::Excluded(&start) => start.saturating_add(1),
+            Bound::Unbounded => 0,
+        };
+        let end = match range.end_bound() {
+            Bound::Included(&end) => end.saturating_add(1),
+            Bound::Excluded(&end) => end,
+            Bound::Unbounded => byte_len,
+        };
+        let start = start.min(byte_len);
+        let end = end.min(byte_len);
+
+        // Index of the character containing the given byte of the slice
+        let base = slice.source.byte_offset(slice.start);
+        let containing = |byte: usize| {
+            if byte >= byte_len {
+                return slice.len();
+            }
+            slice
+                .source
+                .offsets
+                .floor(slice.start, slice.end, base + byte)
+        };
+        let start_char = containing(start);
+        if start >= end {
+            return slice.narrow(start_char, start_char);
+        }
+        let mut end_char = containing(end);
+        if slice.source.byte_offset(slice.start + end_char) - base < end {
+            end_char += 1;
+        }
+        slice.narrow(start_char, end_char)
+    }
+
+    /// Returns a sub-slice of this [`IndexedStr`] based on the given range in terms of the
+    /// _characters_ in the string, or `None` if the range is out of bounds.
+    ///
+    /// Unlike [`slice`](`IndexedStr::slice`), the range is not clamped: `None` is returned if
+    /// the start of the range exceeds its end or the end exceeds [`len`](`IndexedStr::len`),
+    /// analogous to [`str::get`].
+    fn get<R: RangeBounds<usize>>(&self, range: R) -> Option<IndexedSlice<'_>> {
+        let slice = self.as_slice();
+        let start = match range.start_bound() {
+            Bound::Included(&start) => start,
+            Bound::Excluded(&start) => start.checked_add(1)?,
+            Bound::Unbounded => 0,
+        };
+        let end = match range.end_bound() {
+            Bound::Included(&end) => end.checked_add(1)?,
+            Bound::Excluded(&end) => end,
+            Bound::Unbounded => slice.len(),
+        };
+        if start > end || end > slice.len() {
+            return None;
+        }
+        Some(slice.narrow(start, end))
+    }
+
+    /// Divides this [`IndexedStr`] into two slices at the given character index, returning
+    /// the `0..mid` and `mid..len` slices.
+    ///
+    /// `mid` is clamped to [`len`](`IndexedStr::len`), so an out-of-range index yields the
+    /// whole string and an empty slice rather than panicking.
+    fn split_at(&self, mid: usize) -> (IndexedSlice<'_>, IndexedSlice<'_>) {
+        let slice = self.as_slice();
+        let mid = mid.min(slice.len());
+        (slice.narrow(0, mid), slice.narrow(mid, slice.len()))
+    }
+
+    /// Returns an iterator over the characters of this [`IndexedStr`] in order.
+    ///
+    /// Characters are decoded on demand from the underlying string rather than being stored
+    /// separately.
+    fn chars(&self) -> impl Iterator<Item = char> + '_;
+
+    /// Returns a double-ended iterator over the characters of this [`IndexedStr`].
+    ///
+    /// Unlike [`chars`](`IndexedStr::chars`), the returned [`IndexedChars`] can be reversed
+    /// and knows its exact length, since each character is decoded directly from its stored
+    /// offset.
+    fn char_iter(&self) -> IndexedChars<'_> {
+        let slice = self.as_slice();
+        IndexedChars {
+            source: slice.source,
+            start: slice.start,
+            end: slice.end,
+        }
+    }
+
+    /// Returns an iterator over `(char_index, byte_offset, char)` tuples for each character of
+    /// this [`IndexedStr`].
+    ///
+    /// For an [`IndexedSlice`], both the character index and the byte offset are relative to
+    /// the start of the slice, so the byte offsets match those of [`str::char_indices`] on
+    /// [`as_str`](`IndexedStr::as_str`).
+    fn char_indices(&self) -> IndexedCharIndices<'_> {
+        let slice = self.as_slice();
+        IndexedCharIndices {
+            source: slice.source,
+            start: slice.start,
+            index: slice.start,
+            end: slice.end,
+            base: slice.source.byte_offset(slice.start),
+        }
+    }
+
+    /// Returns the character indices at which runs of consecutive characters end, i.e. each
+    /// index `i` for which `same(self.char_at(i - 1), self.char_at(i))` returns `false`.
+    ///
+    /// The start and end of the string are not included, so a string that forms a single run
+    /// yields no boundaries.
+    fn char_run_boundaries<F: FnMut(char, char) -> bool>(&self, mut same: F) -> Vec<usize> {
+        let mut boundaries = Vec::new();
+        let mut chars = self.chars();
+        if let Some(mut prev) = chars.next() {
+            for (i, c) in chars.enumerate() {
+                if !same(prev, c) {
+                    boundaries.push(i + 1);
+                }
+                prev = c;
+            }
+        }
+        boundaries
+    }
 
-    /// Returns a slice containing all characters of this [`IndexedStr`] in order.
-    fn chars(&self) -> &[char];
+    /// Returns an iterator over `(char_index, char)` pairs starting at the given character
+    /// index, allowing a scan to be resumed from a saved position without re-slicing.
+    ///
+    /// The yielded indices continue from `start`, and `start` is clamped to the bounds of the
+    /// [`IndexedStr`].
+    fn chars_from(&self, start: usize) -> impl Iterator<Item = (usize, char)> + '_ {
+        let slice = self.as_slice();
+        let start = start.min(slice.len());
+        slice
+            .narrow(start, slice.len())
+            .str()
+            .chars()
+            .enumerate()
+            .map(move |(i, c)| (start + i, c))
+    }
 
     /// Converts this [`IndexedStr`] into an owned, dynamically allocated [`IndexedString`].
     fn to_indexed_string(&self) -> IndexedString;
@@ -126,459 +362,2554 @@ pub trait IndexedStr:
         self.as_str().to_uppercase().into()
     }
 
-    /// Returns `true` if this [`IndexedStr`] starts with the given string.
-    fn starts_with<S: AsRef<str>>(&self, s: S) -> bool {
-        self.as_str().starts_with(s.as_ref())
+    /// Returns a sub-slice of this [`IndexedStr`] with leading and trailing whitespace removed.
+    ///
+    /// Whitespace is determined by [`char::is_whitespace`]. No allocation is performed.
+    fn trim(&self) -> IndexedSlice<'_> {
+        let slice = self.as_slice();
+        let start = slice.chars().take_while(|c| c.is_whitespace()).count();
+        let trailing = slice
+            .as_str()
+            .chars()
+            .rev()
+            .take_while(|c| c.is_whitespace())
+            .count();
+        slice.narrow(start, (slice.len() - trailing).max(start))
+    }
+
+    /// Returns a sub-slice of this [`IndexedStr`] with leading whitespace removed.
+    ///
+    /// Whitespace is determined by [`char::is_whitespace`]. No allocation is performed.
+    fn trim_start(&self) -> IndexedSlice<'_> {
+        let slice = self.as_slice();
+        let start = slice.chars().take_while(|c| c.is_whitespace()).count();
+        slice.narrow(start, slice.len())
     }
 
-    /// Returns `true` if this [`IndexedStr`] ends with the given string.
-    fn ends_with<S: AsRef<str>>(&self, s: S) -> bool {
-        self.as_str().ends_with(s.as_ref())
+    /// Returns a sub-slice of this [`IndexedStr`] with trailing whitespace removed.
+    ///
+    /// Whitespace is determined by [`char::is_whitespace`]. No allocation is performed.
+    fn trim_end(&self) -> IndexedSlice<'_> {
+        let slice = self.as_slice();
+        let trailing = slice
+            .as_str()
+            .chars()
+            .rev()
+            .take_while(|c| c.is_whitespace())
+            .count();
+        slice.narrow(0, slice.len() - trailing)
+    }
+
+    /// Returns a new [`IndexedString`] with each `'\t'` replaced by the number of spaces
+    /// needed to reach the next tab stop, where tab stops are every `tab_width` columns.
+    ///
+    /// The current column resets at each `'\n'`. Without the `width` feature every character
+    /// occupies one column; with it, columns are measured by display width. A `tab_width` of
+    /// `0` removes tabs entirely.
+    fn expand_tabs(&self, tab_width: usize) -> IndexedString {
+        let mut expanded = String::with_capacity(self.byte_len());
+        let mut column = 0;
+        for c in self.chars() {
+            match c {
+                '\t' => {
+                    let spaces = if tab_width == 0 {
+                        0
+                    } else {
+                        tab_width - column % tab_width
+                    };
+                    for _ in 0..spaces {
+                        expanded.push(' ');
+                    }
+                    column += spaces;
+                }
+                '\n' => {
+                    expanded.push(c);
+                    column = 0;
+                }
+                _ => {
+                    expanded.push(c);
+                    column += char_width(c);
+                }
+            }
+        }
+        IndexedString::from_string(expanded)
     }
 
-    /// Parses this [`IndexedStr`] into a value of type `F` using the [`FromStr`] trait.
-    fn parse<F>(&self) -> Result<F, <F as FromStr>::Err>
-    where
-        F: FromStr,
-    {
-        self.as_str().parse()
+    /// Returns a new [`IndexedString`] with all occurrences of `from` replaced by `to`.
+    fn replace<S: AsRef<str>, R: AsRef<str>>(&self, from: S, to: R) -> IndexedString {
+        self.as_str().replace(from.as_ref(), to.as_ref()).into()
     }
 
-    /// Returns an iterator over the lines of this [`IndexedStr`].
-    fn lines(&self) -> IndexedLines;
-}
-
-/// A [`String`] replacement that allows for safe indexing and slicing of multi-byte characters.
-///
-/// This is the owned counterpart to [`IndexedSlice`].
-#[derive(Clone, Debug, Eq, Hash)]
-pub struct IndexedString {
-    chars: Vec<char>,
-    offsets: Vec<usize>,
-    string: String,
-}
-
-impl IndexedStr for IndexedString {
-    fn as_str(&self) -> &str {
-        &self.string
+    /// Returns a new [`IndexedString`] with the first `count` occurrences of `from` replaced by
+    /// `to`.
+    fn replacen<S: AsRef<str>, R: AsRef<str>>(
+        &self,
+        from: S,
+        to: R,
+        count: usize,
+    ) -> IndexedString {
+        self.as_str()
+            .replacen(from.as_ref(), to.as_ref(), count)
+            .into()
     }
 
-    fn char_at(&self, index: usize) -> Option<char> {
-        self.chars.get(index).copied()
+    /// Returns a new [`IndexedString`] with the first occurrence of `from` replaced by `to`,
+    /// or an unchanged copy if `from` does not occur.
+    fn replace_first<S: AsRef<str>, R: AsRef<str>>(&self, from: S, to: R) -> IndexedString {
+        self.replacen(from, to, 1)
     }
 
-    fn chars(&self) -> &[char] {
-        &self.chars[..]
+    /// Returns a new [`IndexedString`] with the last occurrence of `from` replaced by `to`, or
+    /// an unchanged copy if `from` does not occur.
+    fn replace_last<S: AsRef<str>, R: AsRef<str>>(&self, from: S, to: R) -> IndexedString {
+        let s = self.as_str();
+        let from = from.as_ref();
+        match s.rfind(from) {
+            Some(i) => [&s[..i], to.as_ref(), &s[i + from.len()..]].concat().into(),
+            None => s.into(),
+        }
     }
 
-    fn len(&self) -> usize {
-        self.chars.len()
+    /// Returns a new [`IndexedString`] consisting of this [`IndexedStr`] repeated `n` times.
+    ///
+    /// The index is built by replicating the character offsets of this [`IndexedStr`] rather
+    /// than re-scanning the result. An `n` of `0` yields an empty [`IndexedString`].
+    ///
+    /// # Panics
+    ///
+    /// Like [`str::repeat`], this panics if the resulting byte length would overflow.
+    fn repeat(&self, n: usize) -> IndexedString {
+        let slice = self.as_slice();
+        let string = slice.as_str().repeat(n);
+        let byte_len = slice.byte_len();
+        let base = slice.source.byte_offset(slice.start);
+        let offsets = (0..n)
+            .flat_map(|i| {
+                slice
+                    .source
+                    .offsets
+                    .iter()
+                    .skip(slice.start)
+                    .take(slice.len())
+                    .map(move |offset| offset - base + i * byte_len)
+            })
+            .collect();
+        IndexedString { offsets, string }
     }
 
-    fn byte_len(&self) -> usize {
-        self.string.len()
+    /// Returns a new [`IndexedString`] containing the characters of this [`IndexedStr`]
+    /// sorted in ascending order by [`char`] value.
+    fn sorted_chars(&self) -> IndexedString {
+        let mut chars: Vec<char> = self.chars().collect();
+        chars.sort_unstable();
+        chars.into_iter().collect()
     }
 
-    fn slice<R: RangeBounds<usize>>(&self, range: R) -> IndexedSlice {
-        let start = match range.start_bound() {
-            Bound::Included(&start) => start,
-            Bound::Excluded(&start) => start + 1,
-            Bound::Unbounded => 0,
-        };
-        let end = match range.end_bound() {
-            Bound::Included(&end) => end + 1,
-            Bound::Excluded(&end) => end,
-            Bound::Unbounded => self.chars.len(),
-        };
-        let start = if start > self.chars.len() {
-            self.chars.len()
-        } else {
-            start
-        };
-        let end = if end > self.chars.len() {
-            self.chars.len()
-        } else {
-            end
-        };
+    /// Returns every overlapping run of `n` consecutive characters of this [`IndexedStr`],
+    /// in order, as slices borrowing from the source.
+    ///
+    /// An empty [`Vec`] is returned if `n` is `0` or greater than [`len`](`IndexedStr::len`).
+    fn char_ngrams(&self, n: usize) -> Vec<IndexedSlice<'_>> {
+        let slice = self.as_slice();
+        if n == 0 || n > slice.len() {
+            return Vec::new();
+        }
+        (0..=slice.len() - n)
+            .map(|i| slice.narrow(i, i + n))
+            .collect()
+    }
 
-        IndexedSlice {
-            source: self,
-            start,
-            end,
+    /// Returns the Jaccard index of the sets of character n-grams (see
+    /// [`char_ngrams`](`IndexedStr::char_ngrams`)) of this [`IndexedStr`] and `other`, a
+    /// score between `0.0` (no n-grams in common) and `1.0` (identical n-gram sets).
+    ///
+    /// If neither string has any n-grams (e.g. both are shorter than `n`), the result is `1.0`
+    /// when the strings are equal and `0.0` otherwise.
+    fn jaccard_ngram_similarity<S: IndexedStr>(&self, other: &S, n: usize) -> f64 {
+        let ours: BTreeSet<&str> = self.char_ngrams(n).iter().map(|g| g.str()).collect();
+        let theirs: BTreeSet<&str> = other.char_ngrams(n).iter().map(|g| g.str()).collect();
+        let union = ours.union(&theirs).count();
+        if union == 0 {
+            return if self.as_str() == other.as_str() {
+                1.0
+            } else {
+                0.0
+            };
         }
+        ours.intersection(&theirs).count() as f64 / union as f64
     }
 
-    fn to_indexed_string(&self) -> IndexedString {
-        self.clone()
+    /// Returns a new [`IndexedString`] with the characters of this [`IndexedStr`] in reverse
+    /// order.
+    ///
+    /// This reverses Unicode scalar values, not grapheme clusters, so a combining mark ends
+    /// up before the character it originally modified (e.g. `"e\u{301}x"` becomes
+    /// `"x\u{301}e"`).
+    fn reversed(&self) -> IndexedString {
+        self.as_str().chars().rev().collect()
     }
 
-    fn as_slice(&self) -> IndexedSlice {
-        IndexedSlice {
-            source: self,
-            start: 0,
-            end: self.chars.len(),
+    /// Returns `true` if this [`IndexedStr`] and `other` contain the same characters with
+    /// the same frequencies, in any order.
+    ///
+    /// Characters are compared as whole [`char`]s, so multi-byte characters are never
+    /// confused with one another the way a byte-based comparison could be.
+    ///
+    /// ASCII characters are counted in a fixed-size table, so this runs in O(n) time for
+    /// ASCII text. Other characters are counted in a map, which costs O(n log k) time for `k`
+    /// distinct non-ASCII characters.
+    fn is_anagram_of<S: IndexedStr>(&self, other: &S) -> bool {
+        if self.len() != other.len() {
+            return false;
+        }
+        let mut ascii = [0isize; 128];
+        let mut others: BTreeMap<char, isize> = BTreeMap::new();
+        let mut count = |c: char, delta: isize| match c {
+            c if c.is_ascii() => ascii[c as usize] += delta,
+            c => *others.entry(c).or_default() += delta,
+        };
+        for c in self.chars() {
+            count(c, 1);
         }
+        for c in other.chars() {
+            count(c, -1);
+        }
+        ascii.iter().chain(others.values()).all(|&count| count == 0)
     }
 
-    fn lines(&self) -> IndexedLines {
-        IndexedLines {
-            source: self,
-            start: 0,
-        }
+    /// Returns a sub-slice of this [`IndexedStr`] with all trailing `'\n'` and `'\r'`
+    /// characters removed. Internal line breaks are preserved.
+    fn trim_end_newlines(&self) -> IndexedSlice<'_> {
+        let slice = self.as_slice();
+        let trailing = slice
+            .as_str()
+            .chars()
+            .rev()
+            .take_while(|&c| c == '\n' || c == '\r')
+            .count();
+        slice.narrow(0, slice.len() - trailing)
     }
-}
 
-impl IndexedString {
-    /// Creates a new [`IndexedString`] from a `&str` or anything that implements [`Display`].
-    pub fn from_str(s: impl Display) -> Self {
-        IndexedString::from_string(s.to_string())
+    /// Returns the character index of the first match of the given [`CharPredicate`], or
+    /// `None` if there is no match.
+    ///
+    /// Unlike [`str::find`], the returned index is a character index rather than a byte
+    /// offset. For an [`IndexedSlice`], it is relative to the start of the slice.
+    fn find<P: CharPredicate>(&self, mut pat: P) -> Option<usize> {
+        let slice = self.as_slice();
+        if let Some(literal) = pat.literal() {
+            let byte = slice.as_str().find(literal)?;
+            return slice.char_index_for_byte(byte);
+        }
+        (0..=slice.len()).find(|&i| pat.match_len(slice.rest(i)).is_some())
     }
 
-    /// Creates a new [`IndexedString`] from a [`String`], avoiding the need to clone the
-    /// string by taking ownership of it.
-    pub fn from_string(s: String) -> Self {
-        IndexedString {
-            chars: s.chars().collect(),
-            offsets: s.char_indices().map(|(i, _)| i).collect(),
-            string: s,
+    /// Returns an iterator over the non-overlapping matches of `pat` in this [`IndexedStr`],
+    /// yielding the character index of each match along with a slice of the matched text.
+    ///
+    /// This is analogous to [`str::match_indices`], but reports character indices rather
+    /// than byte offsets. For an [`IndexedSlice`], matches are confined to the slice and their
+    /// indices are relative to its start.
+    fn match_indices<S: AsRef<str>>(&self, pat: S) -> IndexedMatchIndices<'_> {
+        let slice = self.as_slice();
+        let pattern = pat.as_ref();
+        IndexedMatchIndices {
+            source: slice.source,
+            start: slice.start,
+            end: slice.end,
+            search: slice.source.byte_offset(slice.start),
+            end_byte: slice.source.byte_offset(slice.end),
+            pattern: pattern.to_string(),
+            pattern_len: pattern.chars().count(),
+            finished: false,
         }
     }
 
-    /// Creates a new [`IndexedString`] from an iterator of [`char`]s.
-    pub fn from_chars(chars: impl Iterator<Item = char>) -> Self {
-        let chars: Vec<char> = chars.collect();
-        let offsets: Vec<usize> = chars
-            .iter()
-            .scan(0, |acc, &c| {
-                let offset = *acc;
-                *acc += c.len_utf8();
-                Some(offset)
-            })
-            .collect();
-        let string: String = chars.iter().collect();
-        IndexedString {
-            chars,
-            offsets,
-            string,
+    /// Returns the character indices of every whole-word occurrence of `word` in this
+    /// [`IndexedStr`].
+    ///
+    /// An occurrence only counts as a whole word if it is not immediately preceded or
+    /// followed by a word character, i.e. an alphanumeric character or `'_'`, so `"cat"` is
+    /// not found within `"concatenate"`. An empty `word` has no occurrences.
+    fn find_all_words<S: AsRef<str>>(&self, word: S) -> Vec<usize> {
+        let word = word.as_ref();
+        if word.is_empty() {
+            return Vec::new();
         }
+        let is_word_char = |c: char| c.is_alphanumeric() || c == '_';
+        let slice = self.as_slice();
+        slice
+            .match_indices(word)
+            .map(|(i, matched)| (i, i + matched.len()))
+            .filter(|&(start, end)| {
+                let before = start.checked_sub(1).and_then(|i| slice.char_at(i));
+                let after = (end < slice.len()).then(|| slice.char_at(end)).flatten();
+                !before.is_some_and(is_word_char) && !after.is_some_and(is_word_char)
+            })
+            .map(|(start, _)| start)
+            .collect()
     }
-}
 
-impl AsRef<str> for IndexedString {
-    fn as_ref(&self) -> &str {
-        &self.string
+    /// Returns `true` if this [`IndexedStr`] contains a match of the given [`CharPredicate`],
+    /// such as a string, a character or a `FnMut(char) -> bool` closure.
+    fn contains<P: CharPredicate>(&self, pat: P) -> bool {
+        self.find(pat).is_some()
     }
-}
 
-impl Display for IndexedString {
-    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
-        write!(f, "{}", self.string)
+    /// Returns `true` if this [`IndexedStr`] contains the given character.
+    fn contains_char(&self, c: char) -> bool {
+        self.chars().any(|other| other == c)
     }
-}
 
-impl<S: AsRef<str>> PartialEq<S> for IndexedString {
-    fn eq(&self, other: &S) -> bool {
-        self.string == other.as_ref()
+    /// Returns `true` if this [`IndexedStr`] starts with the given string.
+    fn starts_with<S: AsRef<str>>(&self, s: S) -> bool {
+        self.as_str().starts_with(s.as_ref())
     }
-}
 
-/// A [`&str`](`str`) replacement that allows for safe indexing and slicing of multi-byte characters.
-///
-/// This is the borrowed counterpart to [`IndexedString`].
-#[derive(Eq, Debug, Clone)]
-pub struct IndexedSlice<'a> {
-    source: &'a IndexedString,
-    start: usize,
-    end: usize,
-}
+    /// Returns `true` if this [`IndexedStr`] ends with the given string.
+    fn ends_with<S: AsRef<str>>(&self, s: S) -> bool {
+        self.as_str().ends_with(s.as_ref())
+    }
 
-impl<'a> IndexedStr for IndexedSlice<'a> {
-    fn as_str(&self) -> &str {
-        if self.start >= self.source.offsets.len()
-            || self.end > self.source.offsets.len()
-            || self.start > self.end
-        {
-            return "";
+    /// Returns a sub-slice of this [`IndexedStr`] with the given prefix removed, or `None` if
+    /// this [`IndexedStr`] does not start with `prefix`.
+    fn strip_prefix<S: AsRef<str>>(&self, prefix: S) -> Option<IndexedSlice<'_>> {
+        let prefix = prefix.as_ref();
+        if !self.starts_with(prefix) {
+            return None;
         }
-
-        let start_byte = self.source.offsets[self.start];
-        let end_byte = if self.end == self.source.offsets.len() {
-            self.source.string.len()
-        } else {
-            self.source.offsets[self.end]
-        };
-
-        &self.source.string[start_byte..end_byte]
+        let slice = self.as_slice();
+        Some(slice.narrow(prefix.chars().count(), slice.len()))
     }
 
-    fn len(&self) -> usize {
-        self.end - self.start
+    /// Returns a sub-slice of this [`IndexedStr`] with the given suffix removed, or `None` if
+    /// this [`IndexedStr`] does not end with `suffix`.
+    fn strip_suffix<S: AsRef<str>>(&self, suffix: S) -> Option<IndexedSlice<'_>> {
+        let suffix = suffix.as_ref();
+        if !self.ends_with(suffix) {
+            return None;
+        }
+        let slice = self.as_slice();
+        Some(slice.narrow(0, slice.len() - suffix.chars().count()))
     }
 
-    fn byte_len(&self) -> usize {
-        self.source.offsets[self.end] - self.source.offsets[self.start]
+    /// Parses this [`IndexedStr`] into a value of type `F` using the [`FromStr`] trait.
+    fn parse<F>(&self) -> Result<F, <F as FromStr>::Err>
+    where
+        F: FromStr,
+    {
+        self.as_str().parse()
     }
 
-    fn char_at(&self, index: usize) -> Option<char> {
-        self.source.char_at(self.start + index)
-    }
+    /// Returns an iterator over the lines of this [`IndexedStr`].
+    ///
+    /// Lines are terminated by either `'\n'` or `"\r\n"`, and the terminator is not included
+    /// in the yielded slices. A lone `'\r'` that is not followed by `'\n'` is preserved.
+    ///
+    /// For an [`IndexedSlice`], only the text within the slice is split: the last line ends at
+    /// the end of the slice rather than running on into the rest of the underlying source.
+    fn lines(&self) -> IndexedLines<'_>;
 
-    fn slice<R: RangeBounds<usize>>(&self, range: R) -> IndexedSlice {
-        let start = match range.start_bound() {
-            Bound::Included(&start) => start,
-            Bound::Excluded(&start) => start + 1,
-            Bound::Unbounded => 0,
-        };
-        let end = match range.end_bound() {
-            Bound::Included(&end) => end + 1,
-            Bound::Excluded(&end) => end,
-            Bound::Unbounded => self.len(),
-        };
-        let start = if start > self.len() {
-            self.len()
-        } else {
-            start
-        };
-        let end = if end > self.len() { self.len() } else { end };
+    /// Returns an iterator over the sub-slices of this [`IndexedStr`] separated by matches of
+    /// the given [`CharPredicate`], such as a delimiter character or a `FnMut(char) -> bool`
+    /// closure.
+    ///
+    /// This matches the semantics of [`str::split`]: consecutive delimiters and a trailing
+    /// delimiter yield empty slices, and splitting an empty string yields a single empty slice.
+    fn split<P: CharPredicate>(&self, pat: P) -> IndexedSplit<'_, P> {
+        let slice = self.as_slice();
+        IndexedSplit {
+            source: slice.source,
+            start: slice.start,
+            end: slice.end,
+            search: slice.start,
+            pattern: pat,
+            finished: false,
+        }
+    }
 
-        IndexedSlice {
-            source: self.source,
-            start: self.start + start,
-            end: self.start + end,
+    /// Returns an iterator over at most `n` sub-slices of this [`IndexedStr`] separated by
+    /// matches of the given [`CharPredicate`].
+    ///
+    /// This matches the semantics of [`str::splitn`]: the last slice contains the unsplit
+    /// remainder, including any further delimiters, and an `n` of `0` yields nothing.
+    fn splitn<P: CharPredicate>(&self, n: usize, pat: P) -> IndexedSplitN<'_, P> {
+        let slice = self.as_slice();
+        IndexedSplitN {
+            source: slice.source,
+            start: slice.start,
+            end: slice.end,
+            search: slice.start,
+            pattern: pat,
+            remaining: n,
+            reverse: false,
         }
     }
 
-    fn chars(&self) -> &[char] {
-        &self.source.chars[self.start..self.end]
+    /// Returns an iterator over at most `n` sub-slices of this [`IndexedStr`] separated by
+    /// matches of the given [`CharPredicate`], starting from the end.
+    ///
+    /// This matches the semantics of [`str::rsplitn`]: slices are yielded from last to first,
+    /// and the last slice yielded contains the unsplit remainder at the start of the string.
+    fn rsplitn<P: CharPredicate>(&self, n: usize, pat: P) -> IndexedSplitN<'_, P> {
+        let slice = self.as_slice();
+        IndexedSplitN {
+            source: slice.source,
+            start: slice.start,
+            end: slice.end,
+            search: slice.end + 1,
+            pattern: pat,
+            remaining: n,
+            reverse: true,
+        }
+    }
+
+    /// Returns an iterator over the sub-slices of this [`IndexedStr`] separated by the given
+    /// delimiter string.
+    ///
+    /// This behaves like [`split`](`IndexedStr::split`), but takes ownership of a copy of the
+    /// delimiter. As with [`str::split`], an empty delimiter matches at every character
+    /// boundary.
+    fn split_str<S: AsRef<str>>(&self, delimiter: S) -> IndexedSplit<'_> {
+        self.split(String::from(delimiter.as_ref()))
+    }
+
+    /// Returns an iterator over the whitespace-separated words of this [`IndexedStr`].
+    ///
+    /// This matches the semantics of [`str::split_whitespace`]: runs of whitespace (as
+    /// determined by [`char::is_whitespace`]) separate words, and no empty slices are
+    /// yielded for leading, trailing or consecutive whitespace.
+    fn split_whitespace(&self) -> IndexedWords<'_> {
+        let slice = self.as_slice();
+        IndexedWords {
+            source: slice.source,
+            start: slice.start,
+            end: slice.end,
+        }
+    }
+
+    /// Splits this [`IndexedStr`] on `sep`, treating any `sep` that appears between a matched
+    /// pair of `quote` characters as part of the field rather than as a separator.
+    ///
+    /// This is intended for simple CSV or shell-like parsing. The returned fields include
+    /// their quote characters, which can be removed with
+    /// [`strip_prefix`](`IndexedStr::strip_prefix`) and
+    /// [`strip_suffix`](`IndexedStr::strip_suffix`) if desired. An unterminated quote causes
+    /// the remainder of the string to be treated as a single field.
+    fn split_respecting_quotes(&self, sep: char, quote: char) -> Vec<IndexedSlice<'_>> {
+        let slice = self.as_slice();
+        let mut fields = Vec::new();
+        let mut start = 0;
+        let mut quoted = false;
+        for (i, c) in slice.chars().enumerate() {
+            if c == quote {
+                quoted = !quoted;
+            } else if c == sep && !quoted {
+                fields.push(slice.narrow(start, i));
+                start = i + 1;
+            }
+        }
+        fields.push(slice.narrow(start, slice.len()));
+        fields
+    }
+
+    /// Returns the 0-based index of the line containing the character at the given index, or
+    /// `None` if `char_index` is out of range.
+    ///
+    /// A `'\n'` belongs to the line it terminates. `char_index` may also equal
+    /// [`len`](`IndexedStr::len`), e.g. for a caret at the very end, in which case the index
+    /// of the last line is returned.
+    fn line_index_of_char(&self, char_index: usize) -> Option<usize> {
+        if char_index > self.len() {
+            return None;
+        }
+        Some(self.chars().take(char_index).filter(|&c| c == '\n').count())
+    }
+
+    /// Returns the character index of the first character of the given 0-based line, or
+    /// `None` if the line does not exist.
+    ///
+    /// Lines are numbered as in [`lines`](`IndexedStr::lines`), so a trailing `'\n'` starts a
+    /// final empty line whose start index equals [`len`](`IndexedStr::len`).
+    fn char_index_at_line_start(&self, line_index: usize) -> Option<usize> {
+        if line_index == 0 {
+            return Some(0);
+        }
+        self.chars()
+            .enumerate()
+            .filter(|&(_, c)| c == '\n')
+            .nth(line_index - 1)
+            .map(|(i, _)| i + 1)
+    }
+
+    /// Returns the byte length of each line of this [`IndexedStr`], excluding line
+    /// terminators, in the same order as [`lines`](`IndexedStr::lines`).
+    fn line_byte_lengths(&self) -> Vec<usize> {
+        self.lines().map(|line| line.byte_len()).collect()
+    }
+
+    /// Returns the 0-based line number and character range of each line of this
+    /// [`IndexedStr`], in the same order as [`lines`](`IndexedStr::lines`).
+    ///
+    /// The ranges exclude line terminators, and for an [`IndexedSlice`] they are relative to
+    /// the start of the slice.
+    fn line_ranges(&self) -> Vec<(usize, Range<usize>)> {
+        let base = self.as_slice().start;
+        self.lines()
+            .map(|line| line.char_range())
+            .map(|range| range.start - base..range.end - base)
+            .enumerate()
+            .collect()
+    }
+
+    /// Returns the longest whitespace prefix shared by all non-blank lines of this
+    /// [`IndexedStr`], as a slice of the first such line.
+    ///
+    /// Lines that are empty or consist only of whitespace are ignored. Whitespace characters
+    /// must match exactly, so a tab and a space are not considered common indentation. An
+    /// empty slice is returned when there is no common indentation.
+    fn common_indentation(&self) -> IndexedSlice<'_> {
+        let mut indentation: Option<IndexedSlice> = None;
+        for line in self.lines() {
+            let width = line.chars().take_while(|c| c.is_whitespace()).count();
+            if width == line.len() {
+                continue;
+            }
+            indentation = Some(match indentation {
+                None => line.narrow(0, width),
+                Some(current) => {
+                    let common = current
+                        .chars()
+                        .zip(line.chars())
+                        .take_while(|(a, b)| a == b)
+                        .count();
+                    current.narrow(0, common)
+                }
+            });
+        }
+        indentation.unwrap_or_else(|| self.slice(0..0))
+    }
+
+    /// Infers the indentation style of this [`IndexedStr`] from the leading whitespace of its
+    /// lines, returning `None` if no line is indented.
+    ///
+    /// Tabs are reported when more lines are indented with tabs than with spaces. Otherwise
+    /// the indentation width is the most common change in indentation between consecutive
+    /// non-blank lines, preferring the smaller width in the event of a tie.
+    fn indentation_unit(&self) -> Option<IndentKind> {
+        let mut tab_lines = 0;
+        let mut space_lines = 0;
+        let mut previous = 0;
+        let mut deltas: Vec<(usize, usize)> = Vec::new();
+        for line in self.lines() {
+            let width = line.chars().take_while(|c| c.is_whitespace()).count();
+            if width == line.len() {
+                continue;
+            }
+            if line.char_at(0) == Some('\t') {
+                tab_lines += 1;
+                continue;
+            }
+            let spaces = line.chars().take_while(|&c| c == ' ').count();
+            if spaces > 0 {
+                space_lines += 1;
+            }
+            let delta = spaces.abs_diff(previous);
+            previous = spaces;
+            if delta == 0 {
+                continue;
+            }
+            match deltas.iter_mut().find(|(d, _)| *d == delta) {
+                Some((_, count)) => *count += 1,
+                None => deltas.push((delta, 1)),
+            }
+        }
+        if tab_lines == 0 && space_lines == 0 {
+            return None;
+        }
+        if tab_lines > space_lines {
+            return Some(IndentKind::Tabs);
+        }
+        deltas
+            .into_iter()
+            .max_by(|(a, a_count), (b, b_count)| a_count.cmp(b_count).then(b.cmp(a)))
+            .map(|(delta, _)| IndentKind::Spaces(delta))
+    }
+
+    /// Splits this [`IndexedStr`] into consecutive slices of `clusters_per_chunk` extended
+    /// grapheme clusters each, never splitting a cluster.
+    ///
+    /// The last chunk may contain fewer clusters. An empty [`Vec`] is returned if
+    /// `clusters_per_chunk` is `0`.
+    #[cfg(feature = "unicode")]
+    fn grapheme_chunks(&self, clusters_per_chunk: usize) -> Vec<IndexedSlice<'_>> {
+        use unicode_segmentation::UnicodeSegmentation;
+
+        let slice = self.as_slice();
+        let mut chunks = Vec::new();
+        if clusters_per_chunk == 0 {
+            return chunks;
+        }
+        let mut start = 0;
+        let mut end = 0;
+        for (i, grapheme) in slice.as_str().graphemes(true).enumerate() {
+            end += grapheme.chars().count();
+            if (i + 1) % clusters_per_chunk == 0 {
+                chunks.push(slice.narrow(start, end));
+                start = end;
+            }
+        }
+        if start < end {
+            chunks.push(slice.narrow(start, end));
+        }
+        chunks
+    }
+
+    /// Returns a [`SegmentationSummary`] describing how long this [`IndexedStr`] is when
+    /// measured in bytes, characters, grapheme clusters, and UTF-16 code units.
+    #[cfg(feature = "unicode")]
+    fn segmentation_summary(&self) -> SegmentationSummary {
+        use unicode_segmentation::UnicodeSegmentation;
+
+        SegmentationSummary {
+            bytes: self.byte_len(),
+            chars: self.len(),
+            graphemes: self.as_str().graphemes(true).count(),
+            utf16_units: self.chars().map(|c| c.len_utf16()).sum(),
+        }
+    }
+
+    /// Returns an iterator over `(char_index, grapheme_index, char)` tuples, identifying the
+    /// extended grapheme cluster that each character belongs to.
+    #[cfg(feature = "unicode")]
+    fn char_grapheme_indices(&self) -> impl Iterator<Item = (usize, usize, char)> + '_ {
+        use unicode_segmentation::UnicodeSegmentation;
+
+        self.as_str()
+            .graphemes(true)
+            .enumerate()
+            .flat_map(|(g, grapheme)| grapheme.chars().map(move |c| (g, c)))
+            .enumerate()
+            .map(|(i, (g, c))| (i, g, c))
+    }
+
+    /// Returns `true` if this [`IndexedStr`] is a valid identifier according to the Unicode
+    /// XID rules: a non-empty string whose first character is `XID_Start` and whose remaining
+    /// characters are `XID_Continue`.
+    ///
+    /// Note that `'_'` is `XID_Continue` but not `XID_Start`, so a leading underscore is
+    /// rejected.
+    #[cfg(feature = "unicode")]
+    fn is_identifier(&self) -> bool {
+        use unicode_xid::UnicodeXID;
+
+        let mut chars = self.chars();
+        chars.next().is_some_and(|c| c.is_xid_start()) && chars.all(|c| c.is_xid_continue())
+    }
+
+    /// Returns `true` if this [`IndexedStr`] is a valid ASCII identifier: a non-empty string
+    /// whose first character is an ASCII letter and whose remaining characters are ASCII
+    /// letters, digits or `'_'`.
+    ///
+    /// This is the ASCII subset of `is_identifier` and does not require the `unicode` feature.
+    fn is_ascii_identifier(&self) -> bool {
+        let mut chars = self.chars();
+        chars.next().is_some_and(|c| c.is_ascii_alphabetic())
+            && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+    }
+
+    /// Returns an iterator over the bytes of this [`IndexedStr`] in chunks of at most
+    /// `chunk_bytes` bytes, where each chunk ends on a character boundary.
+    ///
+    /// No chunk ever splits a multi-byte character, so every chunk is valid UTF-8 on its own,
+    /// making this suitable for feeding byte-oriented (e.g. SIMD) routines. The last chunk may
+    /// be shorter. If a single character is longer than `chunk_bytes`, that character is
+    /// yielded as its own (longer) chunk rather than being split.
+    fn as_byte_chunks(&self, chunk_bytes: usize) -> IndexedByteChunks<'_> {
+        IndexedByteChunks {
+            string: self.as_str(),
+            start: 0,
+            chunk_bytes,
+        }
+    }
+}
+
+/// The length of an [`IndexedStr`] under each common unit of measure, as returned by
+/// [`segmentation_summary`](`IndexedStr::segmentation_summary`).
+#[cfg(feature = "unicode")]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct SegmentationSummary {
+    /// The number of UTF-8 bytes.
+    pub bytes: usize,
+    /// The number of characters (Unicode scalar values).
+    pub chars: usize,
+    /// The number of extended grapheme clusters.
+    pub graphemes: usize,
+    /// The number of UTF-16 code units.
+    pub utf16_units: usize,
+}
+
+#[cfg(feature = "width")]
+fn char_width(c: char) -> usize {
+    unicode_width::UnicodeWidthChar::width(c).unwrap_or(0)
+}
+
+#[cfg(not(feature = "width"))]
+fn char_width(_c: char) -> usize {
+    1
+}
+
+/// The indentation style of an [`IndexedStr`], as inferred by
+/// [`indentation_unit`](`IndexedStr::indentation_unit`).
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum IndentKind {
+    /// Indentation using the given number of spaces per level.
+    Spaces(usize),
+    /// Indentation using tabs.
+    Tabs,
+}
+
+/// A pattern that can be searched for in an [`IndexedStr`], such as by
+/// [`find`](`IndexedStr::find`), [`split`](`IndexedStr::split`) and
+/// [`contains`](`IndexedStr::contains`).
+///
+/// This is a stable stand-in for the standard library's unstable `Pattern` trait, implemented
+/// for [`char`], string types and `FnMut(char) -> bool` closures.
+pub trait CharPredicate {
+    /// Returns the number of characters matched at the start of `s`, or `None` if `s` does
+    /// not start with a match.
+    fn match_len(&mut self, s: &str) -> Option<usize>;
+
+    /// Returns the fixed string this pattern matches, if it is a string pattern, allowing
+    /// searches to use a substring search rather than testing every character index.
+    fn literal(&self) -> Option<&str> {
+        None
+    }
+}
+
+// Returns the character length of `literal` if `s` starts with it
+fn literal_match_len(literal: &str, s: &str) -> Option<usize> {
+    s.starts_with(literal).then(|| literal.chars().count())
+}
+
+impl CharPredicate for char {
+    fn match_len(&mut self, s: &str) -> Option<usize> {
+        s.starts_with(*self).then_some(1)
+    }
+}
+
+impl<F: FnMut(char) -> bool> CharPredicate for F {
+    fn match_len(&mut self, s: &str) -> Option<usize> {
+        s.chars().next().filter(|&c| self(c)).map(|_| 1)
+    }
+}
+
+impl CharPredicate for &str {
+    fn match_len(&mut self, s: &str) -> Option<usize> {
+        literal_match_len(self, s)
+    }
+
+    fn literal(&self) -> Option<&str> {
+        Some(self)
+    }
+}
+
+impl CharPredicate for String {
+    fn match_len(&mut self, s: &str) -> Option<usize> {
+        literal_match_len(self, s)
+    }
+
+    fn literal(&self) -> Option<&str> {
+        Some(self)
+    }
+}
+
+impl CharPredicate for &String {
+    fn match_len(&mut self, s: &str) -> Option<usize> {
+        literal_match_len(self, s)
+    }
+
+    fn literal(&self) -> Option<&str> {
+        Some(self)
+    }
+}
+
+impl CharPredicate for Box<str> {
+    fn match_len(&mut self, s: &str) -> Option<usize> {
+        literal_match_len(self, s)
+    }
+
+    fn literal(&self) -> Option<&str> {
+        Some(self)
+    }
+}
+
+impl CharPredicate for Cow<'_, str> {
+    fn match_len(&mut self, s: &str) -> Option<usize> {
+        literal_match_len(self, s)
+    }
+
+    fn literal(&self) -> Option<&str> {
+        Some(self)
+    }
+}
+
+impl CharPredicate for IndexedString {
+    fn match_len(&mut self, s: &str) -> Option<usize> {
+        s.starts_with(self.as_str()).then(|| self.len())
+    }
+
+    fn literal(&self) -> Option<&str> {
+        Some(self.as_str())
+    }
+}
+
+impl CharPredicate for &IndexedString {
+    fn match_len(&mut self, s: &str) -> Option<usize> {
+        s.starts_with(self.as_str()).then(|| self.len())
+    }
+
+    fn literal(&self) -> Option<&str> {
+        Some(self.as_str())
+    }
+}
+
+impl CharPredicate for IndexedSlice<'_> {
+    fn match_len(&mut self, s: &str) -> Option<usize> {
+        s.starts_with(self.as_str()).then(|| self.len())
+    }
+
+    fn literal(&self) -> Option<&str> {
+        Some(self.as_str())
+    }
+}
+
+impl CharPredicate for &IndexedSlice<'_> {
+    fn match_len(&mut self, s: &str) -> Option<usize> {
+        s.starts_with(self.as_str()).then(|| self.len())
+    }
+
+    fn literal(&self) -> Option<&str> {
+        Some(self.as_str())
+    }
+}
+
+#[cfg(target_has_atomic = "ptr")]
+impl CharPredicate for OwnedIndexedSlice {
+    fn match_len(&mut self, s: &str) -> Option<usize> {
+        s.starts_with(self.as_str()).then(|| self.len())
+    }
+
+    fn literal(&self) -> Option<&str> {
+        Some(self.as_str())
+    }
+}
+
+/// A [`String`] replacement that allows for safe indexing and slicing of multi-byte characters.
+///
+/// This is the owned counterpart to [`IndexedSlice`].
+///
+/// [`IndexedString`] dereferences to [`str`], so standard string methods such as
+/// [`str::rfind`] are available directly and `&IndexedString` coerces to `&str`. When the
+/// [`IndexedStr`] trait is in scope, its methods take precedence over [`str`] methods of the
+/// same name, because method resolution considers [`IndexedString`] itself before
+/// dereferencing. In particular [`len`](`IndexedStr::len`) returns the number of characters;
+/// without the trait in scope, `len()` resolves to [`str::len`] and returns the number of
+/// bytes.
+#[derive(Clone, Debug, Eq, Hash)]
+pub struct IndexedString {
+    offsets: Offsets,
+    string: String,
+}
+
+impl IndexedStr for IndexedString {
+    fn as_str(&self) -> &str {
+        &self.string
+    }
+
+    fn char_at(&self, index: usize) -> Option<char> {
+        let offset = self.offsets.get(index)?;
+        self.string[offset..].chars().next()
+    }
+
+    fn chars(&self) -> impl Iterator<Item = char> + '_ {
+        self.string.chars()
+    }
+
+    fn len(&self) -> usize {
+        self.offsets.len()
+    }
+
+    fn byte_len(&self) -> usize {
+        self.string.len()
+    }
+
+    fn slice<R: RangeBounds<usize>>(&self, range: R) -> IndexedSlice<'_> {
+        let start = match range.start_bound() {
+            Bound::Included(&start) => start,
+            Bound::Excluded(&start) => start + 1,
+            Bound::Unbounded => 0,
+        };
+        let end = match range.end_bound() {
+            Bound::Included(&end) => end + 1,
+            Bound::Excluded(&end) => end,
+            Bound::Unbounded => self.len(),
+        };
+        let start = if start > self.len() {
+            self.len()
+        } else {
+            start
+        };
+        let end = if end > self.len() { self.len() } else { end };
+        let start = if start > end { end } else { start };
+
+        IndexedSlice {
+            source: self,
+            start,
+            end,
+        }
+    }
+
+    fn to_indexed_string(&self) -> IndexedString {
+        self.clone()
+    }
+
+    fn as_slice(&self) -> IndexedSlice<'_> {
+        IndexedSlice {
+            source: self,
+            start: 0,
+            end: self.len(),
+        }
+    }
+
+    fn lines(&self) -> IndexedLines<'_> {
+        IndexedLines {
+            source: self,
+            start: 0,
+            end: self.len(),
+            terminated: false,
+        }
+    }
+}
+
+impl IndexedString {
+    /// Creates a new [`IndexedString`] from a `&str` or anything that implements [`Display`].
+    #[allow(clippy::should_implement_trait)]
+    pub fn from_str(s: impl Display) -> Self {
+        IndexedString::from_string(s.to_string())
+    }
+
+    /// Creates a new [`IndexedString`] from a [`String`], avoiding the need to clone the
+    /// string by taking ownership of it.
+    pub fn from_string(s: String) -> Self {
+        IndexedString {
+            offsets: s.char_indices().map(|(i, _)| i).collect(),
+            string: s,
+        }
+    }
+
+    /// Reconstructs an [`IndexedString`] from `(char, byte_offset)` pairs, such as those
+    /// persisted from an existing [`IndexedString`].
+    ///
+    /// The offsets are validated against the UTF-8 lengths of the characters: the first
+    /// offset must be `0` and each subsequent offset must immediately follow the previous
+    /// character. A [`ConsistencyError`] describing the first mismatch is returned otherwise.
+    pub fn from_indexed_pairs<I: IntoIterator<Item = (char, usize)>>(
+        pairs: I,
+    ) -> Result<IndexedString, ConsistencyError> {
+        let mut offsets = Offsets::new();
+        let mut string = String::new();
+        for (index, (c, offset)) in pairs.into_iter().enumerate() {
+            if offset != string.len() {
+                return Err(ConsistencyError {
+                    index,
+                    expected: string.len(),
+                    found: offset,
+                });
+            }
+            offsets.push(offset);
+            string.push(c);
+        }
+        Ok(IndexedString { offsets, string })
+    }
+
+    /// Creates a new [`IndexedString`] from an iterator of [`char`]s.
+    pub fn from_chars(chars: impl Iterator<Item = char>) -> Self {
+        IndexedString::from_string(chars.collect())
+    }
+
+    /// Creates a new [`IndexedString`] by concatenating the given parts.
+    ///
+    /// The combined string is built first and indexed only once at the end.
+    pub fn concat<I, S>(parts: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let mut string = String::new();
+        for part in parts {
+            string.push_str(part.as_ref());
+        }
+        IndexedString::from_string(string)
+    }
+
+    /// Creates a new [`IndexedString`] by concatenating the given parts, placing `sep` between
+    /// each adjacent pair.
+    ///
+    /// The combined string is built first and indexed only once at the end.
+    pub fn join<I, S>(parts: I, sep: &str) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let mut string = String::new();
+        for (i, part) in parts.into_iter().enumerate() {
+            if i > 0 {
+                string.push_str(sep);
+            }
+            string.push_str(part.as_ref());
+        }
+        IndexedString::from_string(string)
+    }
+
+    /// Appends the given [`char`] to the end of this [`IndexedString`] in O(1) amortized time.
+    pub fn push(&mut self, c: char) {
+        self.offsets.push(self.string.len());
+        self.string.push(c);
+    }
+
+    /// Appends the given string slice to the end of this [`IndexedString`], indexing only the
+    /// newly added characters.
+    pub fn push_str(&mut self, s: &str) {
+        let base = self.string.len();
+        self.offsets.extend(s.char_indices().map(|(i, _)| base + i));
+        self.string.push_str(s);
+    }
+
+    /// Removes the last character from this [`IndexedString`] and returns it, or `None` if it
+    /// is empty.
+    pub fn pop(&mut self) -> Option<char> {
+        let c = self.string.pop()?;
+        self.offsets.pop();
+        Some(c)
+    }
+
+    /// Removes the last line from this [`IndexedString`] and returns it, or `None` if it is
+    /// empty.
+    ///
+    /// Lines are split the way [`str::lines`] splits them, which differs from
+    /// [`lines`](`IndexedStr::lines`): the returned line does not include its `'\n'` or
+    /// `"\r\n"` terminator, and a trailing terminator ends the last line rather than starting
+    /// a new empty one. It is removed along with that line, so `"a\nb\n"` pops `"b"` and
+    /// leaves `"a\n"`.
+    pub fn pop_line(&mut self) -> Option<IndexedString> {
+        if self.string.is_empty() {
+            return None;
+        }
+        let mut end = self.string.len();
+        if self.string.ends_with('\n') {
+            end -= 1;
+            if self.string[..end].ends_with('\r') {
+                end -= 1;
+            }
+        }
+        let start = self.string[..end].rfind('\n').map_or(0, |i| i + 1);
+        let line = IndexedString::from_str(&self.string[start..end]);
+        let char_start = self
+            .offsets
+            .search(0, self.len(), start)
+            .unwrap_or(self.len());
+        self.string.truncate(start);
+        self.offsets.truncate(char_start);
+        Some(line)
+    }
+
+    /// Inserts the given [`char`] at the given character index, shifting all subsequent
+    /// characters to the right.
+    ///
+    /// An out-of-range `char_index` is clamped, appending `c` to the end.
+    pub fn insert(&mut self, char_index: usize, c: char) {
+        let char_index = char_index.min(self.len());
+        let byte = self.byte_offset(char_index);
+        self.string.insert(byte, c);
+        self.offsets.insert(char_index, byte);
+        self.offsets.shift_up(char_index + 1, c.len_utf8());
+    }
+
+    /// Removes and returns the character at the given character index, shifting all
+    /// subsequent characters to the left.
+    ///
+    /// Returns `None` (leaving this [`IndexedString`] unchanged) if `char_index` is out of
+    /// range, rather than panicking like [`String::remove`].
+    pub fn try_remove(&mut self, char_index: usize) -> Option<char> {
+        if char_index >= self.len() {
+            return None;
+        }
+        let byte = self.offsets.remove(char_index);
+        let c = self.string.remove(byte);
+        self.offsets.shift_down(char_index, c.len_utf8());
+        Some(c)
+    }
+
+    /// Sorts the characters of this [`IndexedString`] in ascending order by [`char`] value, in
+    /// place, rebuilding the index.
+    pub fn sort_chars(&mut self) {
+        *self = self.sorted_chars();
+    }
+
+    /// Returns an [`OwnedIndexedSlice`] of the given range of characters that shares
+    /// ownership of this [`IndexedString`] rather than borrowing it, so it can be freely moved
+    /// and returned.
+    ///
+    /// The range is automatically clamped to the bounds of the [`IndexedString`].
+    #[cfg(target_has_atomic = "ptr")]
+    pub fn arc_slice<R: RangeBounds<usize>>(self: Arc<Self>, range: R) -> OwnedIndexedSlice {
+        let range = self.slice(range).char_range();
+        OwnedIndexedSlice {
+            source: self,
+            start: range.start,
+            end: range.end,
+        }
+    }
+
+    /// Swaps the characters at the given character indices.
+    ///
+    /// When both characters have the same UTF-8 length their bytes are swapped in place and
+    /// the index is left untouched; otherwise the index is rebuilt. Out-of-range indices make
+    /// this a no-op.
+    pub fn swap(&mut self, i: usize, j: usize) {
+        let (i, j) = (i.min(j), i.max(j));
+        let (Some(a), Some(b)) = (self.char_at(i), self.char_at(j)) else {
+            return;
+        };
+        if i == j {
+            return;
+        }
+        let (i_byte, j_byte) = (self.byte_offset(i), self.byte_offset(j));
+        let mut buf = [0; 4];
+        self.string
+            .replace_range(j_byte..j_byte + b.len_utf8(), a.encode_utf8(&mut buf));
+        self.string
+            .replace_range(i_byte..i_byte + a.len_utf8(), b.encode_utf8(&mut buf));
+        if a.len_utf8() != b.len_utf8() {
+            self.offsets = self.string.char_indices().map(|(i, _)| i).collect();
+        }
+    }
+
+    /// Trims leading and trailing whitespace and collapses each internal run of whitespace
+    /// into a single `' '`, in place, rebuilding the index.
+    ///
+    /// Whitespace is determined by [`char::is_whitespace`], so multi-byte whitespace such as
+    /// `'\u{3000}'` (IDEOGRAPHIC SPACE) is handled as well.
+    pub fn normalize_whitespace(&mut self) {
+        let mut normalized = String::with_capacity(self.string.len());
+        for word in self.string.split_whitespace() {
+            if !normalized.is_empty() {
+                normalized.push(' ');
+            }
+            normalized.push_str(word);
+        }
+        *self = IndexedString::from_string(normalized);
+    }
+
+    // Returns the byte offset of the character at `index`, or the byte length of the string
+    // if `index` is at or beyond the end
+    fn byte_offset(&self, index: usize) -> usize {
+        self.offsets.get(index).unwrap_or(self.string.len())
+    }
+}
+
+// The byte offsets of each character of an `IndexedString`. Offsets are stored as `u32`s
+// whenever they fit (i.e. for strings under 4 GiB), which halves their memory footprint on
+// 64-bit targets, and are transparently widened to `usize`s once they no longer fit.
+#[derive(Clone, Debug)]
+enum Offsets {
+    Compact(Vec<u32>),
+    Wide(Vec<usize>),
+}
+
+impl Offsets {
+    fn new() -> Self {
+        Offsets::Compact(Vec::new())
+    }
+
+    fn len(&self) -> usize {
+        match self {
+            Offsets::Compact(offsets) => offsets.len(),
+            Offsets::Wide(offsets) => offsets.len(),
+        }
+    }
+
+    fn get(&self, index: usize) -> Option<usize> {
+        match self {
+            Offsets::Compact(offsets) => offsets.get(index).map(|&offset| offset as usize),
+            Offsets::Wide(offsets) => offsets.get(index).copied(),
+        }
+    }
+
+    fn iter(&self) -> impl Iterator<Item = usize> + '_ {
+        let (compact, wide): (&[u32], &[usize]) = match self {
+            Offsets::Compact(offsets) => (offsets, &[]),
+            Offsets::Wide(offsets) => (&[], offsets),
+        };
+        compact
+            .iter()
+            .map(|&offset| offset as usize)
+            .chain(wide.iter().copied())
+    }
+
+    // Switches to the wide representation if `offset` does not fit in a `u32`
+    fn reserve_offset(&mut self, offset: usize) {
+        if let Offsets::Compact(offsets) = self {
+            if u32::try_from(offset).is_err() {
+                let offsets = core::mem::take(offsets);
+                *self = Offsets::Wide(offsets.into_iter().map(|o| o as usize).collect());
+            }
+        }
+    }
+
+    fn push(&mut self, offset: usize) {
+        self.reserve_offset(offset);
+        match self {
+            Offsets::Compact(offsets) => offsets.push(offset as u32),
+            Offsets::Wide(offsets) => offsets.push(offset),
+        }
+    }
+
+    fn pop(&mut self) -> Option<usize> {
+        match self {
+            Offsets::Compact(offsets) => offsets.pop().map(|offset| offset as usize),
+            Offsets::Wide(offsets) => offsets.pop(),
+        }
+    }
+
+    fn truncate(&mut self, len: usize) {
+        match self {
+            Offsets::Compact(offsets) => offsets.truncate(len),
+            Offsets::Wide(offsets) => offsets.truncate(len),
+        }
+    }
+
+    fn insert(&mut self, index: usize, offset: usize) {
+        self.reserve_offset(offset);
+        match self {
+            Offsets::Compact(offsets) => offsets.insert(index, offset as u32),
+            Offsets::Wide(offsets) => offsets.insert(index, offset),
+        }
+    }
+
+    fn remove(&mut self, index: usize) -> usize {
+        match self {
+            Offsets::Compact(offsets) => offsets.remove(index) as usize,
+            Offsets::Wide(offsets) => offsets.remove(index),
+        }
+    }
+
+    // Adds `amount` to every offset from `index` onward
+    fn shift_up(&mut self, index: usize, amount: usize) {
+        if let Some(last) = self.iter().last() {
+            self.reserve_offset(last + amount);
+        }
+        match self {
+            Offsets::Compact(offsets) => {
+                for offset in &mut offsets[index..] {
+                    *offset += amount as u32;
+                }
+            }
+            Offsets::Wide(offsets) => {
+                for offset in &mut offsets[index..] {
+                    *offset += amount;
+                }
+            }
+        }
+    }
+
+    // Subtracts `amount` from every offset from `index` onward
+    fn shift_down(&mut self, index: usize, amount: usize) {
+        match self {
+            Offsets::Compact(offsets) => {
+                for offset in &mut offsets[index..] {
+                    *offset -= amount as u32;
+                }
+            }
+            Offsets::Wide(offsets) => {
+                for offset in &mut offsets[index..] {
+                    *offset -= amount;
+                }
+            }
+        }
+    }
+
+    // Returns the position of the last offset in `start..end` that does not exceed `target`,
+    // relative to `start`
+    fn floor(&self, start: usize, end: usize, target: usize) -> usize {
+        let count = match self {
+            Offsets::Compact(offsets) => {
+                offsets[start..end].partition_point(|&offset| offset as usize <= target)
+            }
+            Offsets::Wide(offsets) => {
+                offsets[start..end].partition_point(|&offset| offset <= target)
+            }
+        };
+        count.saturating_sub(1)
+    }
+
+    // Returns the position of `target` within the offsets in `start..end`, relative to `start`
+    fn search(&self, start: usize, end: usize, target: usize) -> Option<usize> {
+        match self {
+            Offsets::Compact(offsets) => {
+                let target = u32::try_from(target).ok()?;
+                offsets[start..end].binary_search(&target).ok()
+            }
+            Offsets::Wide(offsets) => offsets[start..end].binary_search(&target).ok(),
+        }
+    }
+}
+
+impl FromIterator<usize> for Offsets {
+    fn from_iter<I: IntoIterator<Item = usize>>(iter: I) -> Self {
+        let mut offsets = Offsets::new();
+        offsets.extend(iter);
+        offsets
+    }
+}
+
+impl Extend<usize> for Offsets {
+    fn extend<I: IntoIterator<Item = usize>>(&mut self, iter: I) {
+        for offset in iter {
+            self.push(offset);
+        }
+    }
+}
+
+impl PartialEq for Offsets {
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len() && self.iter().eq(other.iter())
+    }
+}
+
+impl Eq for Offsets {}
+
+impl core::hash::Hash for Offsets {
+    fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
+        self.len().hash(state);
+        for offset in self.iter() {
+            offset.hash(state);
+        }
+    }
+}
+
+/// An error indicating that a character's byte offset is inconsistent with the UTF-8 lengths
+/// of the characters preceding it.
+///
+/// Returned by [`IndexedString::from_indexed_pairs`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct ConsistencyError {
+    /// The character index at which the inconsistency was found.
+    pub index: usize,
+    /// The byte offset implied by the preceding characters.
+    pub expected: usize,
+    /// The byte offset that was actually provided.
+    pub found: usize,
+}
+
+impl Display for ConsistencyError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "inconsistent byte offset for character {}: expected {}, found {}",
+            self.index, self.expected, self.found
+        )
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ConsistencyError {}
+
+impl AsRef<str> for IndexedString {
+    fn as_ref(&self) -> &str {
+        &self.string
+    }
+}
+
+impl Deref for IndexedString {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        &self.string
+    }
+}
+
+/// Indexes by _character_ range, returning the corresponding [`&str`](`str`).
+///
+/// As with [`slice`](`IndexedStr::slice`), the range is clamped rather than panicking, so
+/// `&s[1..4]` is always valid UTF-8. This takes precedence over the byte-based indexing of
+/// [`str`] that would otherwise be reached through [`Deref`].
+impl Index<Range<usize>> for IndexedString {
+    type Output = str;
+
+    fn index(&self, range: Range<usize>) -> &str {
+        self.slice(range).str()
+    }
+}
+
+impl Display for IndexedString {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{}", self.string)
+    }
+}
+
+impl<S: AsRef<str>> PartialEq<S> for IndexedString {
+    fn eq(&self, other: &S) -> bool {
+        self.string == other.as_ref()
+    }
+}
+
+impl<S: AsRef<str>> PartialOrd<S> for IndexedString {
+    fn partial_cmp(&self, other: &S) -> Option<Ordering> {
+        Some(self.as_str().cmp(other.as_ref()))
+    }
+}
+
+impl Ord for IndexedString {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.as_str().cmp(other.as_str())
+    }
+}
+
+/// A [`&str`](`str`) replacement that allows for safe indexing and slicing of multi-byte characters.
+///
+/// This is the borrowed counterpart to [`IndexedString`].
+///
+/// Like [`IndexedString`], [`IndexedSlice`] dereferences to [`str`], with [`IndexedStr`]
+/// methods taking precedence over [`str`] methods of the same name when the trait is in scope.
+#[derive(Eq, Debug, Clone)]
+pub struct IndexedSlice<'a> {
+    source: &'a IndexedString,
+    start: usize,
+    end: usize,
+}
+
+impl<'a> IndexedSlice<'a> {
+    /// Returns the range of characters this [`IndexedSlice`] covers within the
+    /// [`IndexedString`] it was sliced from.
+    pub fn char_range(&self) -> Range<usize> {
+        self.start..self.end
+    }
+
+    /// Converts this [`IndexedSlice`] into an [`OwnedIndexedSlice`] that does not borrow from
+    /// its source.
+    ///
+    /// Since the source is only borrowed, the characters covered by this slice are copied into
+    /// a new [`IndexedString`]. Use [`IndexedString::arc_slice`] to share an existing buffer
+    /// instead.
+    #[cfg(target_has_atomic = "ptr")]
+    pub fn into_owned_slice(self) -> OwnedIndexedSlice {
+        let len = self.len();
+        OwnedIndexedSlice {
+            source: Arc::new(self.to_indexed_string()),
+            start: 0,
+            end: len,
+        }
+    }
+
+    // Returns the underlying string slice, borrowed from the source rather than `self`
+    fn str(&self) -> &'a str {
+        if self.start >= self.source.offsets.len()
+            || self.end > self.source.offsets.len()
+            || self.start > self.end
+        {
+            return "";
+        }
+
+        let start_byte = self.source.byte_offset(self.start);
+        let end_byte = self.source.byte_offset(self.end);
+
+        &self.source.string[start_byte..end_byte]
+    }
+}
+
+impl<'a> IndexedStr for IndexedSlice<'a> {
+    fn as_str(&self) -> &str {
+        self.str()
+    }
+
+    fn len(&self) -> usize {
+        self.end - self.start
+    }
+
+    fn byte_len(&self) -> usize {
+        self.as_str().len()
+    }
+
+    fn char_at(&self, index: usize) -> Option<char> {
+        self.source.char_at(self.start + index)
+    }
+
+    fn slice<R: RangeBounds<usize>>(&self, range: R) -> IndexedSlice<'_> {
+        let start = match range.start_bound() {
+            Bound::Included(&start) => start,
+            Bound::Excluded(&start) => start + 1,
+            Bound::Unbounded => 0,
+        };
+        let end = match range.end_bound() {
+            Bound::Included(&end) => end + 1,
+            Bound::Excluded(&end) => end,
+            Bound::Unbounded => self.len(),
+        };
+        let start = if start > self.len() {
+            self.len()
+        } else {
+            start
+        };
+        let end = if end > self.len() { self.len() } else { end };
+        let start = if start > end { end } else { start };
+
+        IndexedSlice {
+            source: self.source,
+            start: self.start + start,
+            end: self.start + end,
+        }
+    }
+
+    fn chars(&self) -> impl Iterator<Item = char> + '_ {
+        self.str().chars()
+    }
+
+    fn to_indexed_string(&self) -> IndexedString {
+        IndexedString::from_string(self.as_str().to_string())
+    }
+
+    fn as_slice(&self) -> IndexedSlice<'_> {
+        self.clone()
+    }
+
+    fn lines(&self) -> IndexedLines<'_> {
+        IndexedLines {
+            source: self.source,
+            start: self.start,
+            end: self.end,
+            terminated: false,
+        }
+    }
+}
+
+impl<'a> IndexedSlice<'a> {
+    // Like `slice`, but with unclamped bounds relative to this slice and a result that borrows
+    // from the underlying source rather than from `self`
+    fn narrow(&self, start: usize, end: usize) -> IndexedSlice<'a> {
+        IndexedSlice {
+            source: self.source,
+            start: self.start + start,
+            end: self.start + end,
+        }
+    }
+
+    // Returns the string from character `index` to the end of this slice
+    fn rest(&self, index: usize) -> &'a str {
+        self.narrow(index, self.len()).str()
+    }
+}
+
+impl<'a, S: AsRef<str>> PartialEq<S> for IndexedSlice<'a> {
+    fn eq(&self, other: &S) -> bool {
+        self.as_str() == other.as_ref()
+    }
+}
+
+impl<'a, S: AsRef<str>> PartialOrd<S> for IndexedSlice<'a> {
+    fn partial_cmp(&self, other: &S) -> Option<Ordering> {
+        Some(self.as_str().cmp(other.as_ref()))
+    }
+}
+
+impl<'a> Ord for IndexedSlice<'a> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.as_str().cmp(other.as_str())
+    }
+}
+
+impl<'a> AsRef<str> for IndexedSlice<'a> {
+    fn as_ref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl<'a> Deref for IndexedSlice<'a> {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+/// Indexes by _character_ range relative to the start of this [`IndexedSlice`], clamping the
+/// range like [`Index`] on [`IndexedString`].
+impl<'a> Index<Range<usize>> for IndexedSlice<'a> {
+    type Output = str;
+
+    fn index(&self, range: Range<usize>) -> &str {
+        self.slice(range).str()
+    }
+}
+
+impl<'a> From<&'a IndexedString> for IndexedSlice<'a> {
+    fn from(s: &'a IndexedString) -> Self {
+        IndexedSlice {
+            source: s,
+            start: 0,
+            end: s.len(),
+        }
+    }
+}
+
+impl<'a> From<IndexedSlice<'a>> for IndexedString {
+    fn from(s: IndexedSlice<'a>) -> Self {
+        s.to_indexed_string()
+    }
+}
+
+impl From<String> for IndexedString {
+    fn from(s: String) -> Self {
+        IndexedString::from_string(s)
+    }
+}
+
+impl From<&str> for IndexedString {
+    fn from(s: &str) -> Self {
+        IndexedString::from_str(s)
+    }
+}
+
+impl From<&String> for IndexedString {
+    fn from(s: &String) -> Self {
+        IndexedString::from_string(s.clone())
+    }
+}
+
+impl FromIterator<char> for IndexedString {
+    fn from_iter<I: IntoIterator<Item = char>>(iter: I) -> Self {
+        IndexedString::from_chars(iter.into_iter())
+    }
+}
+
+impl<'a> FromIterator<&'a str> for IndexedString {
+    fn from_iter<I: IntoIterator<Item = &'a str>>(iter: I) -> Self {
+        IndexedString::concat(iter)
+    }
+}
+
+impl Extend<char> for IndexedString {
+    fn extend<I: IntoIterator<Item = char>>(&mut self, iter: I) {
+        for c in iter {
+            self.push(c);
+        }
+    }
+}
+
+impl<'a> Extend<&'a str> for IndexedString {
+    fn extend<I: IntoIterator<Item = &'a str>>(&mut self, iter: I) {
+        for s in iter {
+            self.push_str(s);
+        }
+    }
+}
+
+impl<'a> Display for IndexedSlice<'a> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl IndexedStr for &IndexedString {
+    fn as_str(&self) -> &str {
+        (*self).as_str()
+    }
+
+    fn as_slice(&self) -> IndexedSlice<'_> {
+        (*self).as_slice()
+    }
+
+    fn len(&self) -> usize {
+        (*self).len()
+    }
+
+    fn byte_len(&self) -> usize {
+        (*self).byte_len()
+    }
+
+    fn char_at(&self, index: usize) -> Option<char> {
+        (*self).char_at(index)
+    }
+
+    fn slice<R: RangeBounds<usize>>(&self, range: R) -> IndexedSlice<'_> {
+        (*self).slice(range)
+    }
+
+    fn chars(&self) -> impl Iterator<Item = char> + '_ {
+        (*self).chars()
     }
 
     fn to_indexed_string(&self) -> IndexedString {
-        IndexedString::from_chars(self.chars().into_iter().copied())
+        (*self).to_indexed_string()
     }
 
-    fn as_slice(&self) -> IndexedSlice {
-        self.clone()
+    fn lines(&self) -> IndexedLines<'_> {
+        (*self).lines()
+    }
+}
+
+impl PartialEq<IndexedString> for &IndexedString {
+    fn eq(&self, other: &IndexedString) -> bool {
+        self.as_str() == other.as_str()
+    }
+}
+
+impl PartialEq<IndexedSlice<'_>> for &IndexedString {
+    fn eq(&self, other: &IndexedSlice) -> bool {
+        self.as_str() == other.as_str()
+    }
+}
+
+impl<'a> IndexedStr for &IndexedSlice<'a> {
+    fn as_str(&self) -> &str {
+        (*self).as_str()
+    }
+
+    fn as_slice(&self) -> IndexedSlice<'_> {
+        (*self).as_slice()
+    }
+
+    fn len(&self) -> usize {
+        (*self).len()
+    }
+
+    fn byte_len(&self) -> usize {
+        (*self).byte_len()
+    }
+
+    fn char_at(&self, index: usize) -> Option<char> {
+        (*self).char_at(index)
+    }
+
+    fn slice<R: RangeBounds<usize>>(&self, range: R) -> IndexedSlice<'_> {
+        (*self).slice(range)
+    }
+
+    fn chars(&self) -> impl Iterator<Item = char> + '_ {
+        (*self).chars()
+    }
+
+    fn to_indexed_string(&self) -> IndexedString {
+        (*self).to_indexed_string()
+    }
+
+    fn lines(&self) -> IndexedLines<'_> {
+        (*self).lines()
+    }
+}
+
+impl<'a> PartialEq<IndexedString> for &IndexedSlice<'a> {
+    fn eq(&self, other: &IndexedString) -> bool {
+        self.as_str() == other.as_str()
+    }
+}
+
+impl<'a> PartialEq<IndexedSlice<'_>> for &IndexedSlice<'a> {
+    fn eq(&self, other: &IndexedSlice) -> bool {
+        self.as_str() == other.as_str()
+    }
+}
+
+impl<'a> PartialEq<IndexedSlice<'a>> for &str {
+    fn eq(&self, other: &IndexedSlice) -> bool {
+        other.as_str() == *self
+    }
+}
+
+impl<'a> PartialEq<IndexedSlice<'a>> for String {
+    fn eq(&self, other: &IndexedSlice) -> bool {
+        other.as_str() == *self
+    }
+}
+
+impl PartialEq<IndexedString> for &str {
+    fn eq(&self, other: &IndexedString) -> bool {
+        other.as_str() == *self
+    }
+}
+
+impl PartialEq<IndexedString> for String {
+    fn eq(&self, other: &IndexedString) -> bool {
+        other.as_str() == *self
+    }
+}
+
+/// An owned counterpart to [`IndexedSlice`] that shares its source [`IndexedString`] through
+/// an [`Arc`] instead of borrowing it.
+///
+/// Cloning an [`OwnedIndexedSlice`] only clones the [`Arc`], never the underlying string. See
+/// [`IndexedString::arc_slice`] and [`IndexedSlice::into_owned_slice`].
+///
+/// Since [`Arc`] requires atomic pointer operations, this type is unavailable on targets
+/// without them, such as `thumbv6m-none-eabi`.
+#[cfg(target_has_atomic = "ptr")]
+#[derive(Clone, Debug, Eq)]
+pub struct OwnedIndexedSlice {
+    source: Arc<IndexedString>,
+    start: usize,
+    end: usize,
+}
+
+#[cfg(target_has_atomic = "ptr")]
+impl IndexedStr for OwnedIndexedSlice {
+    fn as_str(&self) -> &str {
+        self.as_slice().str()
+    }
+
+    fn as_slice(&self) -> IndexedSlice<'_> {
+        IndexedSlice {
+            source: &self.source,
+            start: self.start,
+            end: self.end,
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.end - self.start
+    }
+
+    fn byte_len(&self) -> usize {
+        self.as_str().len()
+    }
+
+    fn char_at(&self, index: usize) -> Option<char> {
+        if index >= self.len() {
+            return None;
+        }
+        self.source.char_at(self.start + index)
+    }
+
+    fn slice<R: RangeBounds<usize>>(&self, range: R) -> IndexedSlice<'_> {
+        let slice = self.as_slice();
+        let range = slice.slice(range).char_range();
+        IndexedSlice {
+            source: slice.source,
+            start: range.start,
+            end: range.end,
+        }
+    }
+
+    fn chars(&self) -> impl Iterator<Item = char> + '_ {
+        self.as_str().chars()
+    }
+
+    fn to_indexed_string(&self) -> IndexedString {
+        self.as_slice().to_indexed_string()
+    }
+
+    fn lines(&self) -> IndexedLines<'_> {
+        IndexedLines {
+            source: &self.source,
+            start: self.start,
+            end: self.end,
+            terminated: false,
+        }
+    }
+}
+
+#[cfg(target_has_atomic = "ptr")]
+impl AsRef<str> for OwnedIndexedSlice {
+    fn as_ref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+#[cfg(target_has_atomic = "ptr")]
+impl Deref for OwnedIndexedSlice {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+/// Indexes by _character_ range relative to the start of this [`OwnedIndexedSlice`], clamping
+/// the range like [`Index`] on [`IndexedString`].
+#[cfg(target_has_atomic = "ptr")]
+impl Index<Range<usize>> for OwnedIndexedSlice {
+    type Output = str;
+
+    fn index(&self, range: Range<usize>) -> &str {
+        self.slice(range).str()
     }
+}
 
-    fn lines(&self) -> IndexedLines {
-        IndexedLines {
-            source: self.source,
-            start: self.start,
-        }
+#[cfg(target_has_atomic = "ptr")]
+impl Display for OwnedIndexedSlice {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{}", self.as_str())
     }
 }
 
-impl<'a, S: AsRef<str>> PartialEq<S> for IndexedSlice<'a> {
+#[cfg(target_has_atomic = "ptr")]
+impl<S: AsRef<str>> PartialEq<S> for OwnedIndexedSlice {
     fn eq(&self, other: &S) -> bool {
         self.as_str() == other.as_ref()
     }
 }
 
-impl<'a> AsRef<str> for IndexedSlice<'a> {
-    fn as_ref(&self) -> &str {
-        self.as_str()
+#[cfg(target_has_atomic = "ptr")]
+impl<S: AsRef<str>> PartialOrd<S> for OwnedIndexedSlice {
+    fn partial_cmp(&self, other: &S) -> Option<Ordering> {
+        Some(self.as_str().cmp(other.as_ref()))
     }
 }
 
-impl<'a> From<&'a IndexedString> for IndexedSlice<'a> {
-    fn from(s: &'a IndexedString) -> Self {
-        IndexedSlice {
-            source: s,
-            start: 0,
-            end: s.chars.len(),
-        }
+#[cfg(target_has_atomic = "ptr")]
+impl Ord for OwnedIndexedSlice {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.as_str().cmp(other.as_str())
     }
 }
 
-impl<'a> From<IndexedSlice<'a>> for IndexedString {
+#[cfg(target_has_atomic = "ptr")]
+impl<'a> From<IndexedSlice<'a>> for OwnedIndexedSlice {
     fn from(s: IndexedSlice<'a>) -> Self {
-        s.to_indexed_string()
+        s.into_owned_slice()
     }
 }
 
-impl From<String> for IndexedString {
-    fn from(s: String) -> Self {
-        IndexedString::from_string(s)
+#[cfg(feature = "serde")]
+impl serde::Serialize for IndexedString {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
     }
 }
 
-impl From<&str> for IndexedString {
-    fn from(s: &str) -> Self {
-        IndexedString::from_str(s)
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for IndexedString {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer).map(IndexedString::from_string)
     }
 }
 
-impl From<&String> for IndexedString {
-    fn from(s: &String) -> Self {
-        IndexedString::from_string(s.clone())
+#[cfg(feature = "serde")]
+impl<'a> serde::Serialize for IndexedSlice<'a> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
     }
 }
 
-impl<'a> Display for IndexedSlice<'a> {
-    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
-        write!(f, "{}", self.as_str())
+#[cfg(all(feature = "serde", target_has_atomic = "ptr"))]
+impl serde::Serialize for OwnedIndexedSlice {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
     }
 }
 
-impl IndexedStr for &IndexedString {
-    fn as_str(&self) -> &str {
-        (*self).as_str()
+/// A [`String`] replacement that is indexed by extended grapheme cluster rather than by
+/// character.
+///
+/// Where [`IndexedString`] treats each Unicode scalar value as a unit, so that e.g. `"👋🏽"`
+/// (a waving hand followed by a skin tone modifier) has a length of `2` and can be sliced
+/// between the two, [`IndexedGraphemeString`] treats each user-perceived character as a
+/// unit. Since a grapheme cluster may consist of several [`char`]s, clusters are returned as
+/// [`&str`](`str`)s, and slicing never lands inside a cluster.
+#[cfg(feature = "unicode")]
+#[derive(Clone, Debug, Eq, Hash)]
+pub struct IndexedGraphemeString {
+    offsets: Offsets,
+    string: String,
+}
+
+#[cfg(feature = "unicode")]
+impl IndexedGraphemeString {
+    /// Creates a new [`IndexedGraphemeString`] from a `&str` or anything that implements
+    /// [`Display`].
+    #[allow(clippy::should_implement_trait)]
+    pub fn from_str(s: impl Display) -> Self {
+        IndexedGraphemeString::from_string(s.to_string())
     }
 
-    fn as_slice(&self) -> IndexedSlice {
-        (*self).as_slice()
+    /// Creates a new [`IndexedGraphemeString`] from a [`String`], avoiding the need to clone
+    /// the string by taking ownership of it.
+    pub fn from_string(s: String) -> Self {
+        use unicode_segmentation::UnicodeSegmentation;
+
+        IndexedGraphemeString {
+            offsets: s.grapheme_indices(true).map(|(i, _)| i).collect(),
+            string: s,
+        }
     }
 
-    fn len(&self) -> usize {
-        (*self).len()
+    /// Returns a [`&str`](`str`) representation of this [`IndexedGraphemeString`].
+    pub fn as_str(&self) -> &str {
+        &self.string
     }
 
-    fn byte_len(&self) -> usize {
-        (*self).byte_len()
+    /// Returns the number of grapheme clusters in this [`IndexedGraphemeString`].
+    pub fn len(&self) -> usize {
+        self.offsets.len()
     }
 
-    fn char_at(&self, index: usize) -> Option<char> {
-        (*self).char_at(index)
+    /// Returns `true` if this [`IndexedGraphemeString`] is empty.
+    pub fn is_empty(&self) -> bool {
+        self.offsets.len() == 0
     }
 
-    fn slice<R: RangeBounds<usize>>(&self, range: R) -> IndexedSlice {
-        (*self).slice(range)
+    /// Returns the byte length of this [`IndexedGraphemeString`].
+    pub fn byte_len(&self) -> usize {
+        self.string.len()
     }
 
-    fn chars(&self) -> &[char] {
-        (*self).chars()
+    /// Returns the grapheme cluster at the given index, if it exists.
+    pub fn grapheme_at(&self, index: usize) -> Option<&str> {
+        let start = self.offsets.get(index)?;
+        Some(&self.string[start..self.byte_offset(index + 1)])
     }
 
-    fn to_indexed_string(&self) -> IndexedString {
-        (*self).to_indexed_string()
+    /// Returns the text of the given range of grapheme clusters.
+    ///
+    /// The range is automatically clamped to the bounds of the [`IndexedGraphemeString`].
+    pub fn slice<R: RangeBounds<usize>>(&self, range: R) -> &str {
+        let start = match range.start_bound() {
+            Bound::Included(&start) => start,
+            Bound::Excluded(&start) => start.saturating_add(1),
+            Bound::Unbounded => 0,
+        };
+        let end = match range.end_bound() {
+            Bound::Included(&end) => end.saturating_add(1),
+            Bound::Excluded(&end) => end,
+            Bound::Unbounded => self.len(),
+        };
+        let end = end.min(self.len());
+        let start = start.min(end);
+        &self.string[self.byte_offset(start)..self.byte_offset(end)]
     }
 
-    fn lines(&self) -> IndexedLines {
-        (*self).lines()
+    /// Returns an iterator over the grapheme clusters of this [`IndexedGraphemeString`].
+    pub fn graphemes(&self) -> impl Iterator<Item = &str> + '_ {
+        (0..self.len()).filter_map(|i| self.grapheme_at(i))
     }
-}
 
-impl PartialEq<IndexedString> for &IndexedString {
-    fn eq(&self, other: &IndexedString) -> bool {
-        self.as_str() == other.as_str()
+    // Returns the byte offset of the grapheme cluster at `index`, or the byte length of the
+    // string if `index` is at or beyond the end
+    fn byte_offset(&self, index: usize) -> usize {
+        self.offsets.get(index).unwrap_or(self.string.len())
     }
 }
 
-impl PartialEq<IndexedSlice<'_>> for &IndexedString {
-    fn eq(&self, other: &IndexedSlice) -> bool {
-        self.as_str() == other.as_str()
+#[cfg(feature = "unicode")]
+impl AsRef<str> for IndexedGraphemeString {
+    fn as_ref(&self) -> &str {
+        &self.string
     }
 }
 
-impl<'a> IndexedStr for &IndexedSlice<'a> {
-    fn as_str(&self) -> &str {
-        (*self).as_str()
+#[cfg(feature = "unicode")]
+impl Display for IndexedGraphemeString {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{}", self.string)
     }
+}
 
-    fn as_slice(&self) -> IndexedSlice {
-        (*self).as_slice()
+#[cfg(feature = "unicode")]
+impl<S: AsRef<str>> PartialEq<S> for IndexedGraphemeString {
+    fn eq(&self, other: &S) -> bool {
+        self.string == other.as_ref()
     }
+}
 
-    fn len(&self) -> usize {
-        (*self).len()
+#[cfg(feature = "unicode")]
+impl From<&str> for IndexedGraphemeString {
+    fn from(s: &str) -> Self {
+        IndexedGraphemeString::from_str(s)
     }
+}
 
-    fn byte_len(&self) -> usize {
-        (*self).byte_len()
+#[cfg(feature = "unicode")]
+impl From<String> for IndexedGraphemeString {
+    fn from(s: String) -> Self {
+        IndexedGraphemeString::from_string(s)
     }
+}
 
-    fn char_at(&self, index: usize) -> Option<char> {
-        (*self).char_at(index)
+/// An iterator over the lines of an [`IndexedStr`].
+///
+/// Lines can be taken from either end, so `.rev()` yields the lines from last to first.
+pub struct IndexedLines<'a> {
+    source: &'a IndexedString,
+    start: usize,
+    end: usize,
+    // Whether the character at `end` is a `'\n'` already consumed from the back
+    terminated: bool,
+}
+
+impl<'a> IndexedLines<'a> {
+    // Returns the line spanning `start..end`, excluding the '\r' of a "\r\n" line ending
+    fn line(&self, start: usize, end: usize, terminated: bool) -> IndexedSlice<'a> {
+        if terminated && end > start && self.source.char_at(end - 1) == Some('\r') {
+            return self.source.slice(start..end - 1);
+        }
+        self.source.slice(start..end)
     }
+}
 
-    fn slice<R: RangeBounds<usize>>(&self, range: R) -> IndexedSlice {
-        (*self).slice(range)
+impl<'a> Iterator for IndexedLines<'a> {
+    type Item = IndexedSlice<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.start > self.end {
+            return None;
+        }
+
+        let mut end = self.start;
+        while end < self.end {
+            if self.source.char_at(end) == Some('\n') {
+                let line = self.line(self.start, end, true);
+                self.start = end + 1; // Skip the newline character
+                return Some(line);
+            }
+            end += 1;
+        }
+
+        let line = self.line(self.start, self.end, self.terminated);
+        self.start = self.end + 1; // Mark as finished
+        Some(line)
     }
+}
 
-    fn chars(&self) -> &[char] {
-        (*self).chars()
+impl<'a> DoubleEndedIterator for IndexedLines<'a> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.start > self.end {
+            return None;
+        }
+
+        let mut start = self.end;
+        while start > self.start {
+            if self.source.char_at(start - 1) == Some('\n') {
+                let line = self.line(start, self.end, self.terminated);
+                self.end = start - 1; // Skip the newline character
+                self.terminated = true;
+                return Some(line);
+            }
+            start -= 1;
+        }
+
+        let line = self.line(self.start, self.end, self.terminated);
+        self.start = self.end + 1; // Mark as finished
+        Some(line)
     }
+}
 
-    fn to_indexed_string(&self) -> IndexedString {
-        (*self).to_indexed_string()
+/// A double-ended iterator over the characters of an [`IndexedStr`].
+///
+/// See [`IndexedStr::char_iter`].
+pub struct IndexedChars<'a> {
+    source: &'a IndexedString,
+    start: usize,
+    end: usize,
+}
+
+impl<'a> Iterator for IndexedChars<'a> {
+    type Item = char;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.start >= self.end {
+            return None;
+        }
+        self.start += 1;
+        self.source.char_at(self.start - 1)
     }
 
-    fn lines(&self) -> IndexedLines {
-        (*self).lines()
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.end.saturating_sub(self.start);
+        (len, Some(len))
     }
 }
 
-impl<'a> PartialEq<IndexedString> for &IndexedSlice<'a> {
-    fn eq(&self, other: &IndexedString) -> bool {
-        self.as_str() == other.as_str()
+impl<'a> DoubleEndedIterator for IndexedChars<'a> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.start >= self.end {
+            return None;
+        }
+        self.end -= 1;
+        self.source.char_at(self.end)
     }
 }
 
-impl<'a> PartialEq<IndexedSlice<'_>> for &IndexedSlice<'a> {
-    fn eq(&self, other: &IndexedSlice) -> bool {
-        self.as_str() == other.as_str()
-    }
+impl<'a> ExactSizeIterator for IndexedChars<'a> {}
+
+/// An iterator over the `(char_index, byte_offset, char)` tuples of an [`IndexedStr`].
+///
+/// See [`IndexedStr::char_indices`].
+pub struct IndexedCharIndices<'a> {
+    source: &'a IndexedString,
+    start: usize,
+    index: usize,
+    end: usize,
+    base: usize,
 }
 
-impl<'a> PartialEq<IndexedSlice<'a>> for &str {
-    fn eq(&self, other: &IndexedSlice) -> bool {
-        other.as_str() == *self
+impl<'a> Iterator for IndexedCharIndices<'a> {
+    type Item = (usize, usize, char);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.index >= self.end {
+            return None;
+        }
+        let index = self.index;
+        self.index += 1;
+        Some((
+            index - self.start,
+            self.source.byte_offset(index) - self.base,
+            self.source.char_at(index)?,
+        ))
     }
 }
 
-impl<'a> PartialEq<IndexedSlice<'a>> for String {
-    fn eq(&self, other: &IndexedSlice) -> bool {
-        other.as_str() == *self
-    }
+/// An iterator over character-aligned byte chunks of an [`IndexedStr`].
+///
+/// See [`IndexedStr::as_byte_chunks`].
+pub struct IndexedByteChunks<'a> {
+    string: &'a str,
+    start: usize,
+    chunk_bytes: usize,
 }
 
-impl PartialEq<IndexedString> for &str {
-    fn eq(&self, other: &IndexedString) -> bool {
-        other.as_str() == *self
+impl<'a> Iterator for IndexedByteChunks<'a> {
+    type Item = &'a [u8];
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.start >= self.string.len() {
+            return None;
+        }
+
+        let mut end = self.start + self.chunk_bytes;
+        if end >= self.string.len() {
+            end = self.string.len();
+        } else {
+            while end > self.start && !self.string.is_char_boundary(end) {
+                end -= 1;
+            }
+            if end == self.start {
+                // A single character is wider than the chunk size, so yield it whole
+                end += 1;
+                while !self.string.is_char_boundary(end) {
+                    end += 1;
+                }
+            }
+        }
+
+        let chunk = &self.string.as_bytes()[self.start..end];
+        self.start = end;
+        Some(chunk)
     }
 }
 
-impl PartialEq<IndexedString> for String {
-    fn eq(&self, other: &IndexedString) -> bool {
-        other.as_str() == *self
+/// An iterator over the sub-slices of an [`IndexedStr`] separated by matches of a
+/// [`CharPredicate`].
+///
+/// See [`IndexedStr::split`] and [`IndexedStr::split_str`].
+pub struct IndexedSplit<'a, P = String> {
+    source: &'a IndexedString,
+    start: usize,
+    end: usize,
+    search: usize,
+    pattern: P,
+    finished: bool,
+}
+
+impl<'a, P: CharPredicate> Iterator for IndexedSplit<'a, P> {
+    type Item = IndexedSlice<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.finished {
+            return None;
+        }
+
+        let window = self.source.slice(..self.end);
+        let mut i = self.search;
+        while i <= self.end {
+            if let Some(len) = self.pattern.match_len(window.rest(i)) {
+                let piece = self.source.slice(self.start..i);
+                self.start = i + len;
+                // An empty delimiter matches everywhere, so always make progress
+                self.search = if len == 0 { i + 1 } else { i + len };
+                return Some(piece);
+            }
+            i += 1;
+        }
+
+        self.finished = true; // Mark as finished
+        Some(self.source.slice(self.start..self.end))
     }
 }
 
-/// An iterator over the lines of an [`IndexedStr`].
-pub struct IndexedLines<'a> {
+/// An iterator over a limited number of sub-slices of an [`IndexedStr`] separated by matches
+/// of a [`CharPredicate`].
+///
+/// See [`IndexedStr::splitn`] and [`IndexedStr::rsplitn`].
+pub struct IndexedSplitN<'a, P> {
     source: &'a IndexedString,
     start: usize,
+    end: usize,
+    // The next candidate match position when splitting forward, or one past it in reverse
+    search: usize,
+    pattern: P,
+    remaining: usize,
+    reverse: bool,
 }
 
-impl<'a> Iterator for IndexedLines<'a> {
+impl<'a, P: CharPredicate> Iterator for IndexedSplitN<'a, P> {
     type Item = IndexedSlice<'a>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        if self.start > self.source.chars.len() {
+        if self.remaining == 0 {
             return None;
         }
+        self.remaining -= 1;
+
+        if self.remaining > 0 {
+            let window = self.source.slice(..self.end);
+            if self.reverse {
+                for i in (self.start..self.search).rev() {
+                    if let Some(len) = self.pattern.match_len(window.rest(i)) {
+                        let piece = self.source.slice(i + len..self.end);
+                        self.end = i;
+                        // An empty delimiter matches everywhere, so always make progress
+                        self.search = if len == 0 { i } else { i + 1 };
+                        return Some(piece);
+                    }
+                }
+            } else {
+                for i in self.search..=self.end {
+                    if let Some(len) = self.pattern.match_len(window.rest(i)) {
+                        let piece = self.source.slice(self.start..i);
+                        self.start = i + len;
+                        // An empty delimiter matches everywhere, so always make progress
+                        self.search = if len == 0 { i + 1 } else { i + len };
+                        return Some(piece);
+                    }
+                }
+            }
+        }
+
+        self.remaining = 0; // Mark as finished
+        Some(self.source.slice(self.start..self.end))
+    }
+}
+
+/// An iterator over the character indices and slices of the matches of a pattern in an
+/// [`IndexedStr`].
+///
+/// See [`IndexedStr::match_indices`].
+pub struct IndexedMatchIndices<'a> {
+    source: &'a IndexedString,
+    start: usize,
+    end: usize,
+    // The byte offset to resume searching from
+    search: usize,
+    end_byte: usize,
+    pattern: String,
+    pattern_len: usize,
+    finished: bool,
+}
 
-        if self.start == self.source.chars.len() {
-            self.start += 1; // Mark as finished
-            return Some(self.source.slice(self.start - 1..self.start - 1));
+impl<'a> Iterator for IndexedMatchIndices<'a> {
+    type Item = (usize, IndexedSlice<'a>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.finished {
+            return None;
         }
 
-        let mut end = self.start;
-        while end < self.source.chars.len() {
-            if self.source.chars[end] == '\n' {
-                let line = self.source.slice(self.start..end);
-                self.start = end + 1; // Skip the newline character
-                return Some(line);
+        let haystack = &self.source.string[self.search..self.end_byte];
+        let Some(pos) = haystack.find(self.pattern.as_str()) else {
+            self.finished = true; // Mark as finished
+            return None;
+        };
+        let byte = self.search + pos;
+        let index = if byte == self.end_byte {
+            self.end - self.start
+        } else {
+            self.source.offsets.search(self.start, self.end, byte)?
+        };
+        let start = self.start + index;
+        let matched = self.source.slice(start..start + self.pattern_len);
+
+        if self.pattern.is_empty() {
+            // An empty pattern matches everywhere, so always make progress
+            match haystack[pos..].chars().next() {
+                Some(c) => self.search = byte + c.len_utf8(),
+                None => self.finished = true,
             }
+        } else {
+            self.search = byte + self.pattern.len();
+        }
+        Some((index, matched))
+    }
+}
+
+/// An iterator over the whitespace-separated words of an [`IndexedStr`].
+///
+/// See [`IndexedStr::split_whitespace`].
+pub struct IndexedWords<'a> {
+    source: &'a IndexedString,
+    start: usize,
+    end: usize,
+}
+
+impl<'a> Iterator for IndexedWords<'a> {
+    type Item = IndexedSlice<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let is_word = |i: usize| self.source.char_at(i).is_some_and(|c| !c.is_whitespace());
+
+        while self.start < self.end && !is_word(self.start) {
+            self.start += 1;
+        }
+        if self.start >= self.end {
+            return None;
+        }
+
+        let mut end = self.start + 1;
+        while end < self.end && is_word(end) {
             end += 1;
         }
+        let word = self.source.slice(self.start..end);
+        self.start = end;
+        Some(word)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::*;
+    use alloc::vec;
+    use core::mem::size_of;
+
+    const BOUNDARY: usize = u32::MAX as usize;
 
-        if self.start <= self.source.chars.len() {
-            let line = self.source.slice(self.start..self.source.chars.len());
-            self.start = self.source.chars.len() + 1; // Mark as finished
-            return Some(line);
+    fn offsets_heap_bytes(offsets: &Offsets) -> usize {
+        match offsets {
+            Offsets::Compact(offsets) => offsets.capacity() * size_of::<u32>(),
+            Offsets::Wide(offsets) => offsets.capacity() * size_of::<usize>(),
         }
+    }
 
-        None
+    fn assert_same(compact: &Offsets, wide: &Offsets) {
+        assert_eq!(compact.len(), wide.len());
+        for i in 0..=compact.len() {
+            assert_eq!(compact.get(i), wide.get(i));
+        }
+        assert!(compact.iter().eq(wide.iter()));
+        assert_eq!(compact, wide);
+    }
+
+    #[test]
+    fn test_offsets_compact_by_default() {
+        let offsets: Offsets = [0, 1, 5, BOUNDARY].into_iter().collect();
+        assert!(matches!(offsets, Offsets::Compact(_)));
+        assert_eq!(offsets.get(3), Some(BOUNDARY));
+    }
+
+    #[test]
+    fn test_offsets_memory_large_ascii() {
+        let indexed_string = IndexedString::from_string("a".repeat(16 << 20));
+        assert!(matches!(indexed_string.offsets, Offsets::Compact(_)));
+        let compact = offsets_heap_bytes(&indexed_string.offsets);
+        let wide = offsets_heap_bytes(&Offsets::Wide(indexed_string.offsets.iter().collect()));
+        std::println!("offsets of 16 MiB of ASCII: {compact} bytes compact, {wide} bytes wide");
+        assert_eq!(compact, indexed_string.len() * size_of::<u32>());
+        assert_eq!(wide, indexed_string.len() * size_of::<usize>());
+    }
+
+    #[test]
+    fn test_indexed_string_memory_large_ascii() {
+        let indexed_string = IndexedString::from_string("a".repeat(16 << 20));
+        let string = indexed_string.string.capacity();
+        let total = string + offsets_heap_bytes(&indexed_string.offsets);
+        std::println!(
+            "IndexedString of 16 MiB of ASCII: {total} heap bytes ({string} for the string)"
+        );
+        assert_eq!(total, indexed_string.len() * (1 + size_of::<u32>()));
+    }
+
+    #[test]
+    fn test_offsets_widen_on_push() {
+        let mut compact: Offsets = [0, BOUNDARY - 4].into_iter().collect();
+        let mut wide = Offsets::Wide(vec![0, BOUNDARY - 4]);
+        assert_same(&compact, &wide);
+
+        compact.push(BOUNDARY);
+        wide.push(BOUNDARY);
+        assert!(matches!(compact, Offsets::Compact(_)));
+        assert_same(&compact, &wide);
+
+        compact.push(BOUNDARY + 4);
+        wide.push(BOUNDARY + 4);
+        assert!(matches!(compact, Offsets::Wide(_)));
+        assert_same(&compact, &wide);
+        assert_eq!(compact.search(0, 4, BOUNDARY + 4), Some(3));
+        assert_eq!(compact.pop(), Some(BOUNDARY + 4));
+    }
+
+    #[test]
+    fn test_offsets_widen_on_shift() {
+        let mut compact: Offsets = [0, 4, BOUNDARY - 2].into_iter().collect();
+        let mut wide = Offsets::Wide(vec![0, 4, BOUNDARY - 2]);
+
+        compact.shift_up(1, 2);
+        wide.shift_up(1, 2);
+        assert!(matches!(compact, Offsets::Compact(_)));
+        assert_same(&compact, &wide);
+
+        compact.shift_up(1, 1);
+        wide.shift_up(1, 1);
+        assert!(matches!(compact, Offsets::Wide(_)));
+        assert_same(&compact, &wide);
+        assert_eq!(compact.get(2), Some(BOUNDARY + 1));
+
+        compact.shift_down(1, 3);
+        wide.shift_down(1, 3);
+        assert_same(&compact, &wide);
+        assert_eq!(compact.search(0, 3, 4), Some(1));
+        assert_eq!(compact.search(0, 3, 5), None);
+    }
+
+    #[test]
+    fn test_offsets_widen_on_insert() {
+        let mut compact: Offsets = [0, 1].into_iter().collect();
+        let mut wide = Offsets::Wide(vec![0, 1]);
+        compact.insert(2, BOUNDARY + 1);
+        wide.insert(2, BOUNDARY + 1);
+        assert!(matches!(compact, Offsets::Wide(_)));
+        assert_same(&compact, &wide);
+        assert_eq!(compact.remove(1), 1);
+        assert_eq!(wide.remove(1), 1);
+        assert_same(&compact, &wide);
+    }
+
+    #[test]
+    fn test_offsets_compact_search_out_of_range() {
+        let offsets: Offsets = [0, 1, 2].into_iter().collect();
+        assert_eq!(offsets.search(0, 3, BOUNDARY + 1), None);
+    }
+
+    #[test]
+    fn test_offsets_floor() {
+        let compact: Offsets = [0, 1, 5, 8].into_iter().collect();
+        let wide = Offsets::Wide(vec![0, 1, 5, 8]);
+        for offsets in [&compact, &wide] {
+            assert_eq!(offsets.floor(0, 4, 0), 0);
+            assert_eq!(offsets.floor(0, 4, 3), 1);
+            assert_eq!(offsets.floor(0, 4, 5), 2);
+            assert_eq!(offsets.floor(1, 4, 7), 1);
+            assert_eq!(offsets.floor(0, 4, BOUNDARY + 1), 3);
+        }
     }
 }
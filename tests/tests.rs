@@ -55,6 +55,7 @@ fn test_out_of_bounds_indexing() {
 }
 
 #[test]
+#[allow(clippy::reversed_empty_ranges)]
 fn test_reverse_range() {
     let indexed_string = IndexedString::from_str("hello");
     assert_eq!(indexed_string.slice(3..1), "");
@@ -319,3 +320,1929 @@ fn test_lines_multibyte_characters() {
     assert_eq!(lines[1].as_str(), "こんにちは世界!");
     assert_eq!(lines[2].as_str(), "👋😊");
 }
+
+#[test]
+#[allow(clippy::reversed_empty_ranges)]
+fn test_slice_byte_len() {
+    let indexed_string = IndexedString::from_str("hello");
+    assert_eq!(indexed_string.as_slice().byte_len(), 5);
+    assert_eq!(indexed_string.slice(0..indexed_string.len()).byte_len(), 5);
+    assert_eq!(indexed_string.slice(1..3).byte_len(), 2);
+    assert_eq!(indexed_string.slice(5..5).byte_len(), 0);
+
+    let indexed_string = IndexedString::from_str("a😊b🌍");
+    assert_eq!(indexed_string.as_slice().byte_len(), 10);
+    assert_eq!(indexed_string.slice(0..indexed_string.len()).byte_len(), 10);
+    assert_eq!(indexed_string.slice(1..2).byte_len(), 4);
+    assert_eq!(indexed_string.slice(3..).byte_len(), 4);
+    assert_eq!(indexed_string.slice(4..4).byte_len(), 0);
+    assert_eq!(indexed_string.slice(3..1).byte_len(), 0);
+    assert_eq!(indexed_string.slice(3..1).len(), 0);
+
+    let indexed_string = IndexedString::from_str("");
+    assert_eq!(indexed_string.as_slice().byte_len(), 0);
+}
+
+#[test]
+fn test_as_byte_chunks() {
+    let indexed_string = IndexedString::from_str("a😊b世界c🌍");
+    for chunk_bytes in 1..=12 {
+        let chunks: Vec<&[u8]> = indexed_string.as_byte_chunks(chunk_bytes).collect();
+        for chunk in &chunks {
+            assert!(std::str::from_utf8(chunk).is_ok());
+        }
+        assert_eq!(chunks.concat(), indexed_string.as_str().as_bytes());
+    }
+
+    let chunks: Vec<&[u8]> = indexed_string.as_byte_chunks(4).collect();
+    assert_eq!(
+        chunks,
+        vec![
+            "a".as_bytes(),
+            "😊".as_bytes(),
+            "b世".as_bytes(),
+            "界c".as_bytes(),
+            "🌍".as_bytes()
+        ]
+    );
+
+    let slice = indexed_string.slice(1..4);
+    let chunks: Vec<&[u8]> = slice.as_byte_chunks(6).collect();
+    assert_eq!(chunks, vec!["😊b".as_bytes(), "世".as_bytes()]);
+
+    let empty = IndexedString::from_str("");
+    assert_eq!(empty.as_byte_chunks(4).count(), 0);
+}
+
+#[test]
+fn test_split() {
+    let indexed_string = IndexedString::from_str("a,b,,c,");
+    let pieces: Vec<_> = indexed_string.split(',').collect();
+    assert_eq!(pieces, vec!["a", "b", "", "c", ""]);
+    assert_eq!(pieces, "a,b,,c,".split(',').collect::<Vec<_>>());
+
+    let indexed_string = IndexedString::from_str("");
+    let pieces: Vec<_> = indexed_string.split(',').collect();
+    assert_eq!(pieces, vec![""]);
+
+    let indexed_string = IndexedString::from_str("no delimiter");
+    let pieces: Vec<_> = indexed_string.split(',').collect();
+    assert_eq!(pieces, vec!["no delimiter"]);
+}
+
+#[test]
+fn test_split_multibyte() {
+    let indexed_string = IndexedString::from_str("世界😊こんにちは😊😊👋");
+    let pieces: Vec<_> = indexed_string.split('😊').collect();
+    assert_eq!(pieces, vec!["世界", "こんにちは", "", "👋"]);
+    assert_eq!(pieces[1].len(), 5);
+    assert_eq!(pieces[1].slice(1..3), "んに");
+
+    let slice = indexed_string.slice(1..9);
+    let pieces: Vec<_> = slice.split('😊').collect();
+    assert_eq!(pieces, vec!["界", "こんにちは", ""]);
+}
+
+#[test]
+fn test_split_str() {
+    let indexed_string = IndexedString::from_str("a→→b→→→→c→→");
+    let pieces: Vec<_> = indexed_string.split_str("→→").collect();
+    assert_eq!(pieces, vec!["a", "b", "", "c", ""]);
+    assert_eq!(pieces, "a→→b→→→→c→→".split("→→").collect::<Vec<_>>());
+
+    let indexed_string = IndexedString::from_str("a😊b");
+    let pieces: Vec<_> = indexed_string.split_str("").collect();
+    assert_eq!(pieces, "a😊b".split("").collect::<Vec<_>>());
+
+    let indexed_string = IndexedString::from_str("");
+    let pieces: Vec<_> = indexed_string.split_str("").collect();
+    assert_eq!(pieces, "".split("").collect::<Vec<_>>());
+}
+
+#[test]
+fn test_lines_of_slice() {
+    let indexed_string = IndexedString::from_str("one\ntwo\nthree");
+    let slice = indexed_string.slice(2..6);
+    let lines: Vec<_> = slice.lines().collect();
+    assert_eq!(lines, vec!["e", "tw"]);
+
+    // Every window splits exactly like an owned copy of its text
+    let text = "ab\ncd\r\n\nef";
+    let indexed_string = IndexedString::from_str(text);
+    for start in 0..=indexed_string.len() {
+        for end in start..=indexed_string.len() {
+            let slice = indexed_string.slice(start..end);
+            let owned = slice.to_indexed_string();
+            let expected: Vec<_> = owned.lines().collect();
+            let actual: Vec<_> = slice.lines().collect();
+            assert_eq!(actual, expected, "window: {start}..{end}");
+        }
+    }
+}
+
+#[test]
+fn test_common_indentation_spaces() {
+    let indexed_string = IndexedString::from_str("    fn foo() {\n        bar();\n\n    }\n");
+    let indentation = indexed_string.common_indentation();
+    assert_eq!(indentation, "    ");
+    assert_eq!(indentation.len(), 4);
+
+    let indexed_string = IndexedString::from_str("  a\n    b\n   \n   c");
+    assert_eq!(indexed_string.common_indentation(), "  ");
+}
+
+#[test]
+fn test_common_indentation_tabs() {
+    let indexed_string = IndexedString::from_str("\t\t世界\n\t\t\t😊\n\t\tこんにちは");
+    assert_eq!(indexed_string.common_indentation(), "\t\t");
+
+    let indexed_string = IndexedString::from_str("\tfoo\n    bar");
+    assert_eq!(indexed_string.common_indentation(), "");
+}
+
+#[test]
+fn test_common_indentation_none() {
+    let indexed_string = IndexedString::from_str("foo\n    bar");
+    assert_eq!(indexed_string.common_indentation(), "");
+
+    let indexed_string = IndexedString::from_str("");
+    assert_eq!(indexed_string.common_indentation(), "");
+
+    let indexed_string = IndexedString::from_str("   \n\n  ");
+    assert_eq!(indexed_string.common_indentation(), "");
+}
+
+#[test]
+fn test_trim() {
+    let indexed_string = IndexedString::from_str("  \t hello world \n ");
+    assert_eq!(indexed_string.trim(), "hello world");
+    assert_eq!(indexed_string.trim_start(), "hello world \n ");
+    assert_eq!(indexed_string.trim_end(), "  \t hello world");
+    assert_eq!(indexed_string.trim().len(), 11);
+}
+
+#[test]
+fn test_trim_all_whitespace() {
+    let indexed_string = IndexedString::from_str(" \u{3000}\t\n ");
+    assert_eq!(indexed_string.trim(), "");
+    assert!(indexed_string.trim().is_empty());
+    assert!(indexed_string.trim_start().is_empty());
+    assert!(indexed_string.trim_end().is_empty());
+
+    let indexed_string = IndexedString::from_str("");
+    assert!(indexed_string.trim().is_empty());
+}
+
+#[test]
+fn test_trim_multibyte_whitespace() {
+    let indexed_string = IndexedString::from_str("\u{3000}\u{3000}世界😊\u{3000}");
+    let trimmed = indexed_string.trim();
+    assert_eq!(trimmed, "世界😊");
+    assert_eq!(trimmed.len(), 3);
+    assert_eq!(trimmed.byte_len(), 10);
+    assert_eq!(trimmed.char_at(0), Some('世'));
+    assert_eq!(indexed_string.trim_start(), "世界😊\u{3000}");
+    assert_eq!(indexed_string.trim_end(), "\u{3000}\u{3000}世界😊");
+}
+
+#[test]
+fn test_trim_already_trimmed() {
+    let indexed_string = IndexedString::from_str("a b");
+    assert_eq!(indexed_string.trim(), "a b");
+    assert_eq!(indexed_string.trim_start(), "a b");
+    assert_eq!(indexed_string.trim_end(), "a b");
+}
+
+#[test]
+fn test_trim_slice_stays_in_window() {
+    let indexed_string = IndexedString::from_str("  a  b  c  ");
+    let slice = indexed_string.slice(3..8);
+    assert_eq!(slice, "  b  ");
+    let trimmed = slice.trim();
+    assert_eq!(trimmed, "b");
+    assert_eq!(slice.trim_start(), "b  ");
+    assert_eq!(slice.trim_end(), "  b");
+    assert_eq!(trimmed.to_indexed_string(), "b");
+}
+
+#[test]
+fn test_indentation_unit_two_spaces() {
+    let indexed_string =
+        IndexedString::from_str("a:\n  b:\n    c: 1\n    d: 2\n  e:\n    f: 世界\n");
+    assert_eq!(
+        indexed_string.indentation_unit(),
+        Some(IndentKind::Spaces(2))
+    );
+}
+
+#[test]
+fn test_indentation_unit_four_spaces() {
+    let indexed_string = IndexedString::from_str(
+        "fn main() {\n    if x {\n        y();\n\n        z();\n    }\n}\n\nfn foo() {\n    bar();\n}",
+    );
+    assert_eq!(
+        indexed_string.indentation_unit(),
+        Some(IndentKind::Spaces(4))
+    );
+}
+
+#[test]
+fn test_indentation_unit_tabs() {
+    let indexed_string = IndexedString::from_str("fn main() {\n\tif x {\n\t\ty();\n\t}\n}");
+    assert_eq!(indexed_string.indentation_unit(), Some(IndentKind::Tabs));
+}
+
+#[test]
+fn test_indentation_unit_none() {
+    let indexed_string = IndexedString::from_str("a\nb\n\nc");
+    assert_eq!(indexed_string.indentation_unit(), None);
+    assert_eq!(IndexedString::from_str("").indentation_unit(), None);
+}
+
+#[test]
+fn test_ordering() {
+    let words = [
+        "世界",
+        "apple",
+        "Zebra",
+        "😊",
+        "äpfel",
+        "",
+        "apples",
+        "こんにちは",
+        "a",
+    ];
+
+    let mut expected: Vec<String> = words.iter().map(|w| w.to_string()).collect();
+    expected.sort();
+
+    let mut indexed: Vec<IndexedString> = words.iter().map(IndexedString::from_str).collect();
+    indexed.sort();
+    assert_eq!(indexed, expected);
+
+    let source = IndexedString::from_str(words.join("\n"));
+    let mut slices: Vec<IndexedSlice> = source.lines().collect();
+    slices.sort();
+    assert_eq!(slices, expected);
+}
+
+#[test]
+fn test_ordering_cross_type() {
+    let a = IndexedString::from_str("apple");
+    let b = IndexedString::from_str("banana");
+    let slice = b.slice(0..3);
+    assert!(a < b);
+    assert!(a < slice);
+    assert!(slice > a);
+    assert!(slice < b);
+    assert!(a < "b");
+    assert_eq!(
+        a.partial_cmp(&a.as_slice()),
+        Some(std::cmp::Ordering::Equal)
+    );
+
+    let mut map = std::collections::BTreeMap::new();
+    map.insert(IndexedString::from_str("世界"), 2);
+    map.insert(IndexedString::from_str("hello"), 1);
+    assert_eq!(map.keys().next().unwrap().as_str(), "hello");
+
+    let source = std::sync::Arc::new(IndexedString::from_str("pear\napple\n世界"));
+    let mut owned: Vec<OwnedIndexedSlice> =
+        source.lines().map(IndexedSlice::into_owned_slice).collect();
+    owned.sort();
+    assert_eq!(owned, vec!["apple", "pear", "世界"]);
+    assert!(owned[0] > a.slice(0..2).into_owned_slice());
+    assert!(owned[1] > "apple");
+}
+
+#[test]
+fn test_chars_from() {
+    let indexed_string = IndexedString::from_str("a😊b世界c");
+    let resumed: Vec<_> = indexed_string.chars_from(3).collect();
+    assert_eq!(resumed, vec![(3, '世'), (4, '界'), (5, 'c')]);
+
+    let all: Vec<_> = indexed_string.chars_from(0).collect();
+    assert_eq!(all.len(), indexed_string.len());
+    assert_eq!(all[1], (1, '😊'));
+
+    assert_eq!(indexed_string.chars_from(6).count(), 0);
+    assert_eq!(indexed_string.chars_from(100).count(), 0);
+
+    let slice = indexed_string.slice(1..5);
+    let resumed: Vec<_> = slice.chars_from(2).collect();
+    assert_eq!(resumed, vec![(2, '世'), (3, '界')]);
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_serde_round_trip() {
+    let indexed_string = IndexedString::from_str("Hello, 世界! 👋😊");
+    let json = serde_json::to_string(&indexed_string).unwrap();
+    assert_eq!(json, "\"Hello, 世界! 👋😊\"");
+    let deserialized: IndexedString = serde_json::from_str(&json).unwrap();
+    assert_eq!(deserialized, indexed_string);
+    assert_eq!(deserialized.len(), indexed_string.len());
+    assert_eq!(deserialized.char_at(7), Some('世'));
+    assert_eq!(deserialized.slice(7..9), "世界");
+
+    let empty: IndexedString = serde_json::from_str("\"\"").unwrap();
+    assert!(empty.is_empty());
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_serde_serialize_slice() {
+    let indexed_string = IndexedString::from_str("Hello, 世界! 👋😊");
+    let json = serde_json::to_string(&indexed_string.slice(7..9)).unwrap();
+    assert_eq!(json, "\"世界\"");
+    let deserialized: IndexedString = serde_json::from_str(&json).unwrap();
+    assert_eq!(deserialized, "世界");
+
+    let owned = indexed_string.slice(7..9).into_owned_slice();
+    assert_eq!(serde_json::to_string(&owned).unwrap(), "\"世界\"");
+}
+
+#[test]
+fn test_from_indexed_pairs() {
+    let original = IndexedString::from_str("a😊b世c");
+    let pairs: Vec<(char, usize)> = original
+        .as_str()
+        .char_indices()
+        .map(|(i, c)| (c, i))
+        .collect();
+    let rebuilt = IndexedString::from_indexed_pairs(pairs).unwrap();
+    assert_eq!(rebuilt, original);
+    assert_eq!(rebuilt.len(), 5);
+    assert_eq!(rebuilt.byte_len(), 10);
+    assert_eq!(rebuilt.char_at(3), Some('世'));
+    assert_eq!(rebuilt.slice(1..4), "😊b世");
+
+    let empty = IndexedString::from_indexed_pairs(Vec::new()).unwrap();
+    assert!(empty.is_empty());
+}
+
+#[test]
+fn test_from_indexed_pairs_inconsistent() {
+    let result = IndexedString::from_indexed_pairs(vec![('a', 0), ('😊', 1), ('b', 2)]);
+    let error = result.unwrap_err();
+    assert_eq!(
+        error,
+        ConsistencyError {
+            index: 2,
+            expected: 5,
+            found: 2
+        }
+    );
+    assert_eq!(
+        error.to_string(),
+        "inconsistent byte offset for character 2: expected 5, found 2"
+    );
+
+    let result = IndexedString::from_indexed_pairs(vec![('a', 1)]);
+    assert_eq!(result.unwrap_err().index, 0);
+}
+
+#[test]
+fn test_strip_prefix() {
+    let indexed_string = IndexedString::from_str("😊世界hello");
+    let stripped = indexed_string.strip_prefix("😊世").unwrap();
+    assert_eq!(stripped, "界hello");
+    assert_eq!(stripped.len(), 6);
+    assert_eq!(stripped.char_at(0), Some('界'));
+    assert_eq!(indexed_string.strip_prefix("").unwrap(), "😊世界hello");
+    assert_eq!(indexed_string.strip_prefix("😊世界hello").unwrap(), "");
+    assert!(indexed_string.strip_prefix("世").is_none());
+    assert!(indexed_string.strip_prefix("😊世界hello!").is_none());
+
+    let slice = indexed_string.slice(1..6);
+    assert_eq!(slice.strip_prefix("世界").unwrap(), "hel");
+    assert!(slice.strip_prefix("😊").is_none());
+}
+
+#[test]
+fn test_strip_suffix() {
+    let indexed_string = IndexedString::from_str("hello世界😊");
+    let stripped = indexed_string.strip_suffix("界😊").unwrap();
+    assert_eq!(stripped, "hello世");
+    assert_eq!(stripped.len(), 6);
+    assert_eq!(stripped.char_at(5), Some('世'));
+    assert_eq!(indexed_string.strip_suffix("").unwrap(), "hello世界😊");
+    assert_eq!(indexed_string.strip_suffix("hello世界😊").unwrap(), "");
+    assert!(indexed_string.strip_suffix("界").is_none());
+
+    let slice = indexed_string.slice(2..7);
+    assert_eq!(slice.strip_suffix("o世界").unwrap(), "ll");
+    assert!(slice.strip_suffix("😊").is_none());
+}
+
+#[test]
+fn test_split_respecting_quotes() {
+    let indexed_string = IndexedString::from_str(r#"a,"b,c",d"#);
+    let fields = indexed_string.split_respecting_quotes(',', '"');
+    assert_eq!(fields, vec!["a", r#""b,c""#, "d"]);
+    let unquoted = fields[1].strip_prefix("\"").unwrap();
+    assert_eq!(unquoted.strip_suffix("\"").unwrap(), "b,c");
+
+    let indexed_string = IndexedString::from_str("echo '世界 😊' done");
+    let fields = indexed_string.split_respecting_quotes(' ', '\'');
+    assert_eq!(fields, vec!["echo", "'世界 😊'", "done"]);
+    assert_eq!(fields[1].len(), 6);
+
+    let indexed_string = IndexedString::from_str(r#"a,,"b,c"#);
+    let fields = indexed_string.split_respecting_quotes(',', '"');
+    assert_eq!(fields, vec!["a", "", r#""b,c"#]);
+
+    let indexed_string = IndexedString::from_str("");
+    assert_eq!(indexed_string.split_respecting_quotes(',', '"'), vec![""]);
+}
+
+#[test]
+fn test_split_at() {
+    let indexed_string = IndexedString::from_str("a😊b世界");
+    let (left, right) = indexed_string.split_at(0);
+    assert_eq!(left, "");
+    assert_eq!(right, "a😊b世界");
+
+    let (left, right) = indexed_string.split_at(indexed_string.len());
+    assert_eq!(left, "a😊b世界");
+    assert_eq!(right, "");
+
+    let (left, right) = indexed_string.split_at(1000);
+    assert_eq!(left, "a😊b世界");
+    assert_eq!(right, "");
+
+    let (left, right) = indexed_string.split_at(2);
+    assert_eq!(left, "a😊");
+    assert_eq!(right, "b世界");
+    assert_eq!(left.byte_len(), 5);
+    assert_eq!(right.char_at(1), Some('世'));
+}
+
+#[test]
+fn test_split_at_slice() {
+    let indexed_string = IndexedString::from_str("a😊b世界c");
+    let slice = indexed_string.slice(1..5);
+    let (left, right) = slice.split_at(2);
+    assert_eq!(left, "😊b");
+    assert_eq!(right, "世界");
+
+    let (left, right) = slice.split_at(10);
+    assert_eq!(left, "😊b世界");
+    assert_eq!(right, "");
+}
+
+#[test]
+fn test_char_at_wrapping() {
+    let indexed_string = IndexedString::from_str("a😊世");
+    let len = indexed_string.len();
+    assert_eq!(
+        indexed_string.char_at_wrapping(len),
+        indexed_string.char_at(0)
+    );
+    assert_eq!(indexed_string.char_at_wrapping(1), Some('😊'));
+    assert_eq!(indexed_string.char_at_wrapping(len + 2), Some('世'));
+    assert_eq!(indexed_string.char_at_wrapping(3 * len + 1), Some('😊'));
+
+    let slice = indexed_string.slice(1..3);
+    assert_eq!(slice.char_at_wrapping(2), Some('😊'));
+
+    let empty = IndexedString::from_str("");
+    assert_eq!(empty.char_at_wrapping(0), None);
+    assert_eq!(empty.char_at_wrapping(5), None);
+}
+
+#[test]
+fn test_char_index_for_byte() {
+    // 'a' = 0, '😊' = 1..5, 'b' = 5, '世' = 6..9
+    let indexed_string = IndexedString::from_str("a😊b世");
+    assert_eq!(indexed_string.char_index_for_byte(0), Some(0));
+    assert_eq!(indexed_string.char_index_for_byte(1), Some(1));
+    assert_eq!(indexed_string.char_index_for_byte(2), None);
+    assert_eq!(indexed_string.char_index_for_byte(3), None);
+    assert_eq!(indexed_string.char_index_for_byte(4), None);
+    assert_eq!(indexed_string.char_index_for_byte(5), Some(2));
+    assert_eq!(indexed_string.char_index_for_byte(6), Some(3));
+    assert_eq!(indexed_string.char_index_for_byte(7), None);
+    assert_eq!(indexed_string.char_index_for_byte(9), Some(4));
+    assert_eq!(indexed_string.char_index_for_byte(10), None);
+
+    let (byte, _) = indexed_string
+        .as_str()
+        .char_indices()
+        .find(|(_, c)| *c == 'b')
+        .unwrap();
+    assert_eq!(indexed_string.char_index_for_byte(byte), Some(2));
+
+    let slice = indexed_string.slice(1..3);
+    assert_eq!(slice.char_index_for_byte(0), Some(0));
+    assert_eq!(slice.char_index_for_byte(2), None);
+    assert_eq!(slice.char_index_for_byte(4), Some(1));
+    assert_eq!(slice.char_index_for_byte(5), Some(2));
+    assert_eq!(slice.char_index_for_byte(6), None);
+    assert_eq!(slice.char_index_for_byte(usize::MAX), None);
+    assert_eq!(
+        indexed_string.slice(1..).char_index_for_byte(usize::MAX),
+        None
+    );
+
+    let empty = IndexedString::from_str("");
+    assert_eq!(empty.char_index_for_byte(0), Some(0));
+    assert_eq!(empty.char_index_for_byte(1), None);
+}
+
+#[test]
+fn test_byte_index_for_char() {
+    let indexed_string = IndexedString::from_str("a😊b世");
+    assert_eq!(indexed_string.byte_index_for_char(0), Some(0));
+    assert_eq!(indexed_string.byte_index_for_char(1), Some(1));
+    assert_eq!(indexed_string.byte_index_for_char(2), Some(5));
+    assert_eq!(indexed_string.byte_index_for_char(3), Some(6));
+    assert_eq!(indexed_string.byte_index_for_char(4), Some(9));
+    assert_eq!(indexed_string.byte_index_for_char(5), None);
+
+    let slice = indexed_string.slice(1..3);
+    assert_eq!(slice.byte_index_for_char(0), Some(0));
+    assert_eq!(slice.byte_index_for_char(1), Some(4));
+    assert_eq!(slice.byte_index_for_char(2), Some(5));
+    assert_eq!(slice.byte_index_for_char(3), None);
+
+    for i in 0..=indexed_string.len() {
+        let byte = indexed_string.byte_index_for_char(i).unwrap();
+        assert_eq!(indexed_string.char_index_for_byte(byte), Some(i));
+    }
+}
+
+#[cfg(feature = "unicode")]
+#[test]
+fn test_grapheme_chunks() {
+    // family (man + ZWJ + woman + ZWJ + girl), rainbow flag, and a skin-toned wave
+    let indexed_string = IndexedString::from_str("👨\u{200d}👩\u{200d}👧🏳\u{fe0f}\u{200d}🌈👋🏽a");
+    let chunks = indexed_string.grapheme_chunks(2);
+    assert_eq!(chunks.len(), 2);
+    assert_eq!(chunks[0], "👨\u{200d}👩\u{200d}👧🏳\u{fe0f}\u{200d}🌈");
+    assert_eq!(chunks[1], "👋🏽a");
+    assert_eq!(chunks[0].len(), 9);
+
+    let chunks = indexed_string.grapheme_chunks(1);
+    assert_eq!(chunks.len(), 4);
+    assert_eq!(chunks[0], "👨\u{200d}👩\u{200d}👧");
+    assert_eq!(chunks[2], "👋🏽");
+
+    let chunks = indexed_string.grapheme_chunks(10);
+    assert_eq!(chunks.len(), 1);
+    assert_eq!(chunks[0], indexed_string);
+
+    assert!(indexed_string.grapheme_chunks(0).is_empty());
+    assert!(IndexedString::from_str("").grapheme_chunks(3).is_empty());
+}
+
+#[test]
+fn test_push_and_pop() {
+    let mut indexed_string = IndexedString::from_str("a");
+    indexed_string.push('😊');
+    assert_eq!(indexed_string, "a😊");
+    assert_eq!(indexed_string.len(), 2);
+    assert_eq!(indexed_string.byte_len(), 5);
+    assert_eq!(indexed_string.char_at(1), Some('😊'));
+
+    indexed_string.push_str("世界b");
+    assert_eq!(indexed_string, "a😊世界b");
+    assert_eq!(indexed_string.len(), 5);
+    assert_eq!(indexed_string.byte_len(), 12);
+    assert_eq!(indexed_string.slice(2..4), "世界");
+
+    assert_eq!(indexed_string.pop(), Some('b'));
+    assert_eq!(indexed_string.pop(), Some('界'));
+    assert_eq!(indexed_string, "a😊世");
+    assert_eq!(indexed_string.len(), 3);
+    assert_eq!(indexed_string.byte_len(), 8);
+    assert_eq!(indexed_string.slice(1..), "😊世");
+
+    indexed_string.push('é');
+    assert_eq!(indexed_string, "a😊世é");
+    assert_eq!(indexed_string.slice(3..), "é");
+    assert_eq!(indexed_string.byte_len(), 10);
+    assert_eq!(indexed_string, IndexedString::from_str("a😊世é"));
+
+    assert_eq!(indexed_string.pop(), Some('é'));
+    assert_eq!(indexed_string.pop(), Some('世'));
+    assert_eq!(indexed_string.pop(), Some('😊'));
+    assert_eq!(indexed_string.pop(), Some('a'));
+    assert_eq!(indexed_string.pop(), None);
+    assert!(indexed_string.is_empty());
+    assert_eq!(indexed_string.byte_len(), 0);
+
+    indexed_string.push_str("");
+    assert!(indexed_string.is_empty());
+    indexed_string.push_str("👋");
+    assert_eq!(indexed_string.char_at(0), Some('👋'));
+}
+
+#[test]
+fn test_expand_tabs() {
+    let indexed_string = IndexedString::from_str("\ta\tbc\tdef\tg\n\tx\t");
+    let expanded = indexed_string.expand_tabs(4);
+    assert_eq!(expanded, "    a   bc  def g\n    x   ");
+    assert_eq!(expanded.len(), expanded.as_str().chars().count());
+    assert_eq!(expanded.char_at(4), Some('a'));
+
+    assert_eq!(indexed_string.expand_tabs(0), "abcdefg\nx");
+    assert_eq!(IndexedString::from_str("no tabs").expand_tabs(8), "no tabs");
+
+    let slice = indexed_string.slice(2..6);
+    assert_eq!(slice.expand_tabs(4), "    bc  ");
+}
+
+#[test]
+fn test_expand_tabs_multibyte() {
+    let indexed_string = IndexedString::from_str("é\tx\n世\ty");
+    let expanded = indexed_string.expand_tabs(4);
+    assert!(expanded.starts_with("é   x\n"));
+    #[cfg(feature = "width")]
+    assert_eq!(expanded, "é   x\n世  y");
+    #[cfg(not(feature = "width"))]
+    assert_eq!(expanded, "é   x\n世   y");
+    assert_eq!(expanded.slice(0..5), "é   x");
+}
+
+#[test]
+fn test_insert() {
+    let mut indexed_string = IndexedString::from_str("ab");
+    indexed_string.insert(1, '😊');
+    assert_eq!(indexed_string, "a😊b");
+    assert_eq!(indexed_string.len(), 3);
+    assert_eq!(indexed_string.byte_len(), 6);
+    assert_eq!(indexed_string.char_at(2), Some('b'));
+    assert_eq!(indexed_string.slice(1..2), "😊");
+    assert_eq!(indexed_string.slice(2..), "b");
+
+    indexed_string.insert(0, '世');
+    assert_eq!(indexed_string, "世a😊b");
+    assert_eq!(indexed_string.slice(2..), "😊b");
+
+    indexed_string.insert(100, '!');
+    assert_eq!(indexed_string, "世a😊b!");
+    assert_eq!(indexed_string.char_at(4), Some('!'));
+    assert_eq!(indexed_string, IndexedString::from_str("世a😊b!"));
+}
+
+#[test]
+fn test_try_remove() {
+    let mut indexed_string = IndexedString::from_str("a世😊b");
+    assert_eq!(indexed_string.try_remove(1), Some('世'));
+    assert_eq!(indexed_string, "a😊b");
+    assert_eq!(indexed_string.len(), 3);
+    assert_eq!(indexed_string.byte_len(), 6);
+    assert_eq!(indexed_string.slice(1..), "😊b");
+
+    assert_eq!(indexed_string.try_remove(3), None);
+    assert_eq!(indexed_string, "a😊b");
+
+    assert_eq!(indexed_string.try_remove(1), Some('😊'));
+    assert_eq!(indexed_string.slice(1..), "b");
+    assert_eq!(indexed_string.try_remove(0), Some('a'));
+    assert_eq!(indexed_string.try_remove(0), Some('b'));
+    assert_eq!(indexed_string.try_remove(0), None);
+    assert!(indexed_string.is_empty());
+}
+
+#[test]
+fn test_replace() {
+    let indexed_string = IndexedString::from_str("a世界b世界c");
+    let replaced = indexed_string.replace("世界", "-");
+    assert_eq!(replaced, "a-b-c");
+    assert_eq!(replaced.len(), 5);
+    assert_eq!(replaced.byte_len(), 5);
+    assert_eq!(replaced.char_at(4), Some('c'));
+
+    let indexed_string = IndexedString::from_str("a-b-c");
+    let replaced = indexed_string.replace("-", "😊😊");
+    assert_eq!(replaced, "a😊😊b😊😊c");
+    assert_eq!(replaced.len(), 7);
+    assert_eq!(replaced.byte_len(), 19);
+    assert_eq!(replaced.char_at(3), Some('b'));
+    assert_eq!(replaced.slice(4..6), "😊😊");
+
+    assert_eq!(indexed_string.replace("x", "y"), "a-b-c");
+    assert_eq!(indexed_string.slice(1..4).replace("-", "世"), "世b世");
+}
+
+#[test]
+fn test_replacen() {
+    let indexed_string = IndexedString::from_str("😊a😊b😊c");
+    let replaced = indexed_string.replacen("😊", "", 2);
+    assert_eq!(replaced, "ab😊c");
+    assert_eq!(replaced.len(), 4);
+    assert_eq!(replaced.char_at(2), Some('😊'));
+    assert_eq!(indexed_string.replacen("😊", "x", 0), indexed_string);
+    assert_eq!(indexed_string.replacen("😊", "世界", 10), "世界a世界b世界c");
+}
+
+#[cfg(feature = "unicode")]
+#[test]
+fn test_segmentation_summary() {
+    // 'e' followed by a combining acute accent forms a single grapheme cluster
+    let indexed_string = IndexedString::from_str("a世e\u{301}😊");
+    let summary = indexed_string.segmentation_summary();
+    assert_eq!(
+        summary,
+        SegmentationSummary {
+            bytes: 11,
+            chars: 5,
+            graphemes: 4,
+            utf16_units: 6,
+        }
+    );
+    assert_eq!(summary.chars, indexed_string.len());
+    assert_eq!(
+        summary.utf16_units,
+        indexed_string.as_str().encode_utf16().count()
+    );
+
+    let summary = indexed_string.slice(2..4).segmentation_summary();
+    assert_eq!(summary.bytes, 3);
+    assert_eq!(summary.chars, 2);
+    assert_eq!(summary.graphemes, 1);
+    assert_eq!(summary.utf16_units, 2);
+
+    let summary = IndexedString::from_str("").segmentation_summary();
+    assert_eq!(summary.graphemes, 0);
+}
+
+#[test]
+fn test_lines_crlf() {
+    let indexed_string = IndexedString::from_str("one\r\ntwo\nthree\r\n\r\nfour");
+    let lines: Vec<_> = indexed_string.lines().collect();
+    assert_eq!(lines, vec!["one", "two", "three", "", "four"]);
+    assert_eq!(lines[2].len(), 5);
+
+    let indexed_string = IndexedString::from_str("\r\n");
+    let lines: Vec<_> = indexed_string.lines().collect();
+    assert_eq!(lines, vec!["", ""]);
+
+    let indexed_string = IndexedString::from_str("世界\r\n😊\r\n");
+    let lines: Vec<_> = indexed_string.lines().collect();
+    assert_eq!(lines, vec!["世界", "😊", ""]);
+}
+
+#[test]
+fn test_lines_lone_carriage_return() {
+    let indexed_string = IndexedString::from_str("a\rb\nc\r");
+    let lines: Vec<_> = indexed_string.lines().collect();
+    assert_eq!(lines, vec!["a\rb", "c\r"]);
+
+    let indexed_string = IndexedString::from_str("\r\r\n");
+    let lines: Vec<_> = indexed_string.lines().collect();
+    assert_eq!(lines, vec!["\r", ""]);
+}
+
+#[test]
+fn test_line_index_of_char() {
+    // "世界" = 0..2, '\n' = 2, "" = 3 ('\n'), "😊a" = 4..6, '\n' = 6, "b" = 7
+    let indexed_string = IndexedString::from_str("世界\n\n😊a\nb");
+    assert_eq!(indexed_string.line_index_of_char(0), Some(0));
+    assert_eq!(indexed_string.line_index_of_char(1), Some(0));
+    assert_eq!(indexed_string.line_index_of_char(2), Some(0));
+    assert_eq!(indexed_string.line_index_of_char(3), Some(1));
+    assert_eq!(indexed_string.line_index_of_char(4), Some(2));
+    assert_eq!(indexed_string.line_index_of_char(6), Some(2));
+    assert_eq!(indexed_string.line_index_of_char(7), Some(3));
+    assert_eq!(indexed_string.line_index_of_char(8), Some(3));
+    assert_eq!(indexed_string.line_index_of_char(9), None);
+
+    let empty = IndexedString::from_str("");
+    assert_eq!(empty.line_index_of_char(0), Some(0));
+    assert_eq!(empty.line_index_of_char(1), None);
+}
+
+#[test]
+fn test_deref_to_str() {
+    fn byte_count(s: &str) -> usize {
+        s.len()
+    }
+
+    let indexed_string = IndexedString::from_str("a😊世界");
+    assert!(indexed_string.contains("世"));
+    assert_eq!(indexed_string.rfind('世'), Some(5));
+    assert!(indexed_string.is_char_boundary(5));
+    assert_eq!(byte_count(&indexed_string), 11);
+
+    let slice = indexed_string.slice(1..3);
+    assert_eq!(slice.rfind('😊'), Some(0));
+    assert_eq!(slice.rfind('界'), None);
+    assert_eq!(byte_count(&slice), 7);
+}
+
+#[test]
+fn test_deref_len_is_char_count() {
+    let indexed_string = IndexedString::from_str("a😊世界");
+    assert_eq!(indexed_string.len(), 4);
+    assert_eq!(indexed_string.byte_len(), 11);
+    assert_eq!(str::len(&indexed_string), 11);
+
+    let slice = indexed_string.slice(1..3);
+    assert_eq!(slice.len(), 2);
+    assert_eq!(str::len(&slice), 7);
+}
+
+#[test]
+fn test_line_byte_lengths() {
+    let indexed_string = IndexedString::from_str("世界\nabc\r\n😊a\n\né");
+    assert_eq!(indexed_string.line_byte_lengths(), vec![6, 3, 5, 0, 2]);
+    let char_lengths: Vec<usize> = indexed_string.lines().map(|line| line.len()).collect();
+    assert_eq!(char_lengths, vec![2, 3, 2, 0, 1]);
+
+    assert_eq!(IndexedString::from_str("").line_byte_lengths(), vec![0]);
+    assert_eq!(
+        IndexedString::from_str("😊\n").line_byte_lengths(),
+        vec![4, 0]
+    );
+}
+
+#[test]
+#[allow(clippy::reversed_empty_ranges)]
+fn test_get() {
+    let indexed_string = IndexedString::from_str("hello");
+    assert!(indexed_string.get(3..1).is_none());
+    assert!(indexed_string.get(0..100).is_none());
+    assert!(indexed_string.get(6..).is_none());
+    assert!(indexed_string.get(..=5).is_none());
+    assert_eq!(indexed_string.get(1..3).unwrap(), "el");
+    assert_eq!(indexed_string.get(..).unwrap(), "hello");
+    assert_eq!(indexed_string.get(5..).unwrap(), "");
+    assert_eq!(indexed_string.get(..=4).unwrap(), "hello");
+}
+
+#[test]
+fn test_get_multibyte() {
+    let indexed_string = IndexedString::from_str("a😊世界b");
+    assert_eq!(indexed_string.get(1..3).unwrap(), "😊世");
+    assert_eq!(indexed_string.get(1..3).unwrap().byte_len(), 7);
+    assert!(indexed_string.get(1..6).is_none());
+    assert_eq!(indexed_string.get(1..5).unwrap(), "😊世界b");
+
+    let slice = indexed_string.slice(1..4);
+    assert_eq!(slice.get(1..3).unwrap(), "世界");
+    assert!(slice.get(1..4).is_none());
+    assert_eq!(slice.get(0..0).unwrap(), "");
+}
+
+#[test]
+fn test_trim_end_newlines() {
+    let indexed_string = IndexedString::from_str("text\n\n\n");
+    assert_eq!(indexed_string.trim_end_newlines(), "text");
+
+    let indexed_string = IndexedString::from_str("line 1\r\n世界 \r\n\r\n");
+    assert_eq!(indexed_string.trim_end_newlines(), "line 1\r\n世界 ");
+    assert_eq!(indexed_string.trim_end_newlines().len(), 11);
+
+    let indexed_string = IndexedString::from_str("no trailing newlines\n😊");
+    assert_eq!(
+        indexed_string.trim_end_newlines(),
+        "no trailing newlines\n😊"
+    );
+
+    assert_eq!(IndexedString::from_str("\n\r\n").trim_end_newlines(), "");
+    assert_eq!(IndexedString::from_str("").trim_end_newlines(), "");
+}
+
+#[test]
+fn test_concat() {
+    let source = IndexedString::from_str("ab😊世界");
+    let parts = vec![source.slice(3..5), source.slice(0..1), source.slice(2..3)];
+    let concatenated = IndexedString::concat(&parts);
+    assert_eq!(concatenated, "世界a😊");
+    assert_eq!(concatenated.len(), 4);
+    assert_eq!(concatenated.char_at(3), Some('😊'));
+    assert_eq!(concatenated.slice(1..3), "界a");
+
+    assert_eq!(IndexedString::concat(["x", "", "y"]), "xy");
+    assert!(IndexedString::concat(Vec::<&str>::new()).is_empty());
+}
+
+#[test]
+fn test_join() {
+    let source = IndexedString::from_str("ab😊世界");
+    let parts = vec![source.slice(0..2), source.slice(2..3), source.slice(3..5)];
+    let joined = IndexedString::join(&parts, "→");
+    assert_eq!(joined, "ab→😊→世界");
+    assert_eq!(joined.len(), 7);
+    assert_eq!(joined.byte_len(), 18);
+    assert_eq!(joined.char_at(2), Some('→'));
+    assert_eq!(joined.char_at(3), Some('😊'));
+    assert_eq!(joined.slice(5..), "世界");
+
+    assert_eq!(IndexedString::join(["only"], "→"), "only");
+    assert_eq!(IndexedString::join(["", ""], "→"), "→");
+    assert!(IndexedString::join(Vec::<&str>::new(), "→").is_empty());
+}
+
+#[test]
+fn test_normalize_whitespace() {
+    let mut indexed_string =
+        IndexedString::from_str(" \t世界\t\t  hello\u{3000}\u{3000}😊 \n end\u{3000}");
+    indexed_string.normalize_whitespace();
+    assert_eq!(indexed_string, "世界 hello 😊 end");
+    assert_eq!(indexed_string.len(), 14);
+    assert_eq!(indexed_string.byte_len(), 21);
+    assert_eq!(indexed_string.char_at(9), Some('😊'));
+    assert_eq!(indexed_string.slice(3..8), "hello");
+    assert_eq!(indexed_string, IndexedString::from_str("世界 hello 😊 end"));
+
+    let mut indexed_string = IndexedString::from_str(" \u{3000}\t ");
+    indexed_string.normalize_whitespace();
+    assert!(indexed_string.is_empty());
+}
+
+#[test]
+fn test_char_indices() {
+    let indexed_string = IndexedString::from_str("a😊b世界é");
+    let indices: Vec<_> = indexed_string.char_indices().collect();
+    assert_eq!(
+        indices,
+        vec![
+            (0, 0, 'a'),
+            (1, 1, '😊'),
+            (2, 5, 'b'),
+            (3, 6, '世'),
+            (4, 9, '界'),
+            (5, 12, 'é')
+        ]
+    );
+    let expected: Vec<_> = indexed_string.as_str().char_indices().collect();
+    let actual: Vec<_> = indexed_string
+        .char_indices()
+        .map(|(_, b, c)| (b, c))
+        .collect();
+    assert_eq!(actual, expected);
+}
+
+#[test]
+fn test_char_indices_slice() {
+    let indexed_string = IndexedString::from_str("a😊b世界é");
+    let slice = indexed_string.slice(1..5);
+    let indices: Vec<_> = slice.char_indices().collect();
+    assert_eq!(
+        indices,
+        vec![(0, 0, '😊'), (1, 4, 'b'), (2, 5, '世'), (3, 8, '界')]
+    );
+    let expected: Vec<_> = slice.as_str().char_indices().collect();
+    let actual: Vec<_> = slice.char_indices().map(|(_, b, c)| (b, c)).collect();
+    assert_eq!(actual, expected);
+
+    assert_eq!(indexed_string.slice(6..).char_indices().count(), 0);
+}
+
+#[test]
+fn test_char_index_at_line_start() {
+    let indexed_string = IndexedString::from_str("世界😊\nabc\r\n\né\n");
+    assert_eq!(indexed_string.char_index_at_line_start(0), Some(0));
+    assert_eq!(indexed_string.char_index_at_line_start(1), Some(4));
+    assert_eq!(indexed_string.char_at(4), Some('a'));
+    assert_eq!(indexed_string.char_index_at_line_start(2), Some(9));
+    assert_eq!(indexed_string.char_index_at_line_start(3), Some(10));
+    assert_eq!(indexed_string.char_at(10), Some('é'));
+    assert_eq!(indexed_string.char_index_at_line_start(4), Some(12));
+    assert_eq!(indexed_string.char_index_at_line_start(5), None);
+
+    for (i, line) in indexed_string.lines().enumerate() {
+        let start = indexed_string.char_index_at_line_start(i).unwrap();
+        assert_eq!(indexed_string.slice(start..start + line.len()), line);
+    }
+
+    let empty = IndexedString::from_str("");
+    assert_eq!(empty.char_index_at_line_start(0), Some(0));
+    assert_eq!(empty.char_index_at_line_start(1), None);
+}
+
+#[test]
+fn test_contains() {
+    let indexed_string = IndexedString::from_str("a😊b世界");
+    assert!(indexed_string.contains("😊b"));
+    assert!(indexed_string.contains("世界"));
+    assert!(indexed_string.contains(""));
+    assert!(!indexed_string.contains("界世"));
+    assert!(indexed_string.contains(IndexedString::from_str("b世")));
+
+    let slice = indexed_string.slice(1..4);
+    assert!(slice.contains("😊b世"));
+    assert!(!slice.contains("界"));
+    assert!(!slice.contains("a"));
+}
+
+#[test]
+fn test_contains_char() {
+    let indexed_string = IndexedString::from_str("a😊b世界");
+    assert!(indexed_string.contains_char('😊'));
+    assert!(indexed_string.contains_char('界'));
+    assert!(!indexed_string.contains_char('c'));
+
+    let slice = indexed_string.slice(1..4);
+    assert!(slice.contains_char('😊'));
+    assert!(slice.contains_char('世'));
+    assert!(!slice.contains_char('界'));
+    assert!(!slice.contains_char('a'));
+    assert!(!IndexedString::from_str("").contains_char('a'));
+}
+
+#[cfg(feature = "unicode")]
+#[test]
+fn test_char_grapheme_indices() {
+    // 'e' + combining acute accent, then 'x', then 'a' + combining ring + combining dot below
+    let indexed_string = IndexedString::from_str("e\u{301}xa\u{30a}\u{323}");
+    let indices: Vec<_> = indexed_string.char_grapheme_indices().collect();
+    assert_eq!(
+        indices,
+        vec![
+            (0, 0, 'e'),
+            (1, 0, '\u{301}'),
+            (2, 1, 'x'),
+            (3, 2, 'a'),
+            (4, 2, '\u{30a}'),
+            (5, 2, '\u{323}')
+        ]
+    );
+
+    let slice = indexed_string.slice(1..);
+    let indices: Vec<_> = slice.char_grapheme_indices().collect();
+    assert_eq!(indices[0], (0, 0, '\u{301}'));
+    assert_eq!(indices[1], (1, 1, 'x'));
+}
+
+#[test]
+fn test_chars_decoded_from_offsets() {
+    let mut indexed_string = IndexedString::from_str("a世😊b");
+    assert!(indexed_string.chars().eq("a世😊b".chars()));
+    assert!(indexed_string.slice(1..3).chars().eq("世😊".chars()));
+    assert_eq!(indexed_string.char_at(2), Some('😊'));
+
+    indexed_string.insert(1, 'é');
+    assert_eq!(indexed_string.try_remove(3), Some('😊'));
+    indexed_string.push('界');
+    assert_eq!(indexed_string, "aé世b界");
+    assert!(indexed_string.chars().eq("aé世b界".chars()));
+    for (i, c) in "aé世b界".chars().enumerate() {
+        assert_eq!(indexed_string.char_at(i), Some(c));
+    }
+    assert_eq!(indexed_string.pop(), Some('界'));
+    assert_eq!(indexed_string.char_at(4), None);
+}
+
+#[test]
+fn test_pop_line() {
+    let mut indexed_string = IndexedString::from_str("first\nsecond\r\nthird");
+    assert_eq!(indexed_string.pop_line().unwrap(), "third");
+    assert_eq!(indexed_string, "first\nsecond\r\n");
+    assert_eq!(indexed_string.pop_line().unwrap(), "second");
+    assert_eq!(indexed_string, "first\n");
+    assert_eq!(indexed_string.pop_line().unwrap(), "first");
+    assert_eq!(indexed_string, "");
+    assert_eq!(indexed_string.pop_line(), None);
+
+    let mut indexed_string = IndexedString::from_str("a\n\n");
+    assert_eq!(indexed_string.pop_line().unwrap(), "");
+    assert_eq!(indexed_string.pop_line().unwrap(), "a");
+    assert_eq!(indexed_string.pop_line(), None);
+
+    for s in ["x\ny\n", "x\r\n\ny", "\n", "x\n\n\n"] {
+        let mut indexed_string = IndexedString::from_str(s);
+        let mut popped = Vec::new();
+        while let Some(line) = indexed_string.pop_line() {
+            popped.push(line.as_str().to_string());
+        }
+        popped.reverse();
+        assert_eq!(popped, s.lines().collect::<Vec<_>>(), "{s:?}");
+    }
+}
+
+#[test]
+fn test_pop_line_multibyte() {
+    let mut indexed_string = IndexedString::from_str("hello\n世界😊\n");
+    let line = indexed_string.pop_line().unwrap();
+    assert_eq!(line, "世界😊");
+    assert_eq!(line.len(), 3);
+    assert_eq!(line.char_at(2), Some('😊'));
+    assert_eq!(indexed_string, "hello\n");
+    assert_eq!(indexed_string.len(), 6);
+    assert_eq!(indexed_string.char_at(5), Some('\n'));
+    assert_eq!(indexed_string.char_at(6), None);
+    indexed_string.push('é');
+    assert_eq!(indexed_string.char_at(6), Some('é'));
+}
+
+#[test]
+fn test_byte_slice() {
+    // 'a' is byte 0, '😊' is bytes 1..5 and 'b' is byte 5
+    let indexed_string = IndexedString::from_str("a😊b");
+    assert_eq!(indexed_string.byte_slice(0..1), "a");
+    assert_eq!(indexed_string.byte_slice(1..5), "😊");
+    assert_eq!(indexed_string.byte_slice(2..3), "😊");
+    assert_eq!(indexed_string.byte_slice(0..2), "a😊");
+    assert_eq!(indexed_string.byte_slice(3..), "😊b");
+    assert_eq!(indexed_string.byte_slice(..=1), "a😊");
+    assert_eq!(indexed_string.byte_slice(5..5), "");
+    assert_eq!(indexed_string.byte_slice(4..100), "😊b");
+    assert_eq!(indexed_string.byte_slice(10..20), "");
+    assert_eq!(indexed_string.byte_slice(..), "a😊b");
+
+    let slice = indexed_string.byte_slice(2..4);
+    assert_eq!(slice.len(), 1);
+    assert!(std::str::from_utf8(slice.as_str().as_bytes()).is_ok());
+}
+
+#[test]
+#[allow(clippy::reversed_empty_ranges)]
+fn test_byte_slice_empty_and_reversed() {
+    let indexed_string = IndexedString::from_str("a😊b");
+    for range in [2..2, 3..3, 5..2, 4..1] {
+        let slice = indexed_string.byte_slice(range.clone());
+        assert_eq!(slice, "", "{range:?}");
+    }
+    assert_eq!(indexed_string.byte_slice(2..2).char_range(), 1..1);
+    assert_eq!(indexed_string.byte_slice(5..2).char_range(), 2..2);
+    assert_eq!(indexed_string.byte_slice(4..1).char_range(), 1..1);
+    assert_eq!(indexed_string.byte_slice(100..100).char_range(), 3..3);
+    assert_eq!(indexed_string.byte_slice(1..1), "");
+    assert_eq!(indexed_string.byte_slice(2..=2), "😊");
+}
+
+#[test]
+fn test_byte_slice_relative_to_slice() {
+    let indexed_string = IndexedString::from_str("x世😊界y");
+    let slice = indexed_string.slice(1..4);
+    assert_eq!(slice, "世😊界");
+    assert_eq!(slice.byte_slice(0..1), "世");
+    assert_eq!(slice.byte_slice(4..5), "😊");
+    assert_eq!(slice.byte_slice(6..8), "😊界");
+    assert_eq!(slice.byte_slice(8..), "界");
+    assert_eq!(slice.byte_slice(5..100), "😊界");
+}
+
+#[test]
+fn test_char_run_boundaries() {
+    fn category(c: char) -> u8 {
+        if c.is_alphabetic() {
+            0
+        } else if c.is_numeric() {
+            1
+        } else {
+            2
+        }
+    }
+    let same = |a: char, b: char| category(a) == category(b);
+
+    let indexed_string = IndexedString::from_str("aa11bb");
+    assert_eq!(indexed_string.char_run_boundaries(same), vec![2, 4]);
+    assert_eq!(
+        IndexedString::from_str("世界12 😊").char_run_boundaries(same),
+        vec![2, 4]
+    );
+    assert_eq!(
+        indexed_string.slice(1..5).char_run_boundaries(same),
+        vec![1, 3]
+    );
+    assert!(indexed_string
+        .slice(0..2)
+        .char_run_boundaries(same)
+        .is_empty());
+    assert!(IndexedString::from_str("")
+        .char_run_boundaries(same)
+        .is_empty());
+    assert_eq!(
+        IndexedString::from_str("aab").char_run_boundaries(|a, b| a == b),
+        vec![2]
+    );
+}
+
+#[test]
+fn test_from_iterator() {
+    let indexed_string: IndexedString = "a世b界😊".chars().filter(|c| !c.is_ascii()).collect();
+    assert_eq!(indexed_string, "世界😊");
+    assert_eq!(indexed_string.len(), 3);
+    assert_eq!(indexed_string.char_at(2), Some('😊'));
+
+    let indexed_string: IndexedString = ["héllo", ", ", "世界"].into_iter().collect();
+    assert_eq!(indexed_string, "héllo, 世界");
+    assert_eq!(indexed_string.char_at(7), Some('世'));
+
+    let indexed_string: IndexedString = "abc".chars().filter(|c| *c != 'b').collect();
+    assert_eq!(indexed_string, "ac");
+}
+
+#[test]
+fn test_extend() {
+    let mut indexed_string = IndexedString::from_str("héllo");
+    indexed_string.extend(", 世".chars());
+    indexed_string.extend(["界", "", "😊!"]);
+    assert_eq!(indexed_string, "héllo, 世界😊!");
+    assert_eq!(indexed_string.len(), 11);
+    assert_eq!(indexed_string.char_at(8), Some('界'));
+    assert_eq!(indexed_string.char_at(9), Some('😊'));
+    assert_eq!(indexed_string.slice(7..10), "世界😊");
+}
+
+#[test]
+fn test_replace_first_and_last() {
+    let path = IndexedString::from_str("usr/local/日本/bin");
+    assert_eq!(path.replace_first("/", "::"), "usr::local/日本/bin");
+    assert_eq!(path.replace_last("/", "::"), "usr/local/日本::bin");
+    assert_eq!(path.replace_first("?", "!"), path);
+    assert_eq!(path.replace_last("?", "!"), path);
+
+    let replaced = path.replace_last("/", "😊");
+    assert_eq!(replaced.len(), path.len());
+    assert_eq!(replaced.char_at(12), Some('😊'));
+
+    let slice = path.slice(4..12);
+    assert_eq!(slice, "local/日本");
+    assert_eq!(slice.replace_first("/", ""), "local日本");
+    assert_eq!(slice.replace_last("本", "本/"), "local/日本/");
+}
+
+#[test]
+fn test_lines_rev() {
+    for input in [
+        "",
+        "\n",
+        "a",
+        "a\n",
+        "a\nb",
+        "a\nb\n",
+        "a\n\nb\n\n",
+        "first\r\nsecond\r\n",
+        "lone\rcarriage\r",
+        "\r\n\r\n",
+        "世界\n😊\r\nend",
+    ] {
+        let indexed_string = IndexedString::from_str(input);
+        let mut forward: Vec<_> = indexed_string.lines().collect();
+        forward.reverse();
+        let backward: Vec<_> = indexed_string.lines().rev().collect();
+        assert_eq!(backward, forward, "input: {input:?}");
+    }
+
+    let indexed_string = IndexedString::from_str("zero\none\r\ntwo\nthree\r\n");
+    // The window ends in a '\r' whose '\n' lies outside of the slice
+    let slice = indexed_string.slice(1..9);
+    let mut forward: Vec<_> = slice.lines().collect();
+    assert_eq!(forward, vec!["ero", "one\r"]);
+    forward.reverse();
+    assert_eq!(slice.lines().rev().collect::<Vec<_>>(), forward);
+}
+
+#[test]
+fn test_lines_meet_in_the_middle() {
+    let indexed_string = IndexedString::from_str("a\r\nb\nc\r\nd\n");
+    let mut lines = indexed_string.lines();
+    assert_eq!(lines.next().unwrap(), "a");
+    assert_eq!(lines.next_back().unwrap(), "");
+    assert_eq!(lines.next_back().unwrap(), "d");
+    assert_eq!(lines.next().unwrap(), "b");
+    assert_eq!(lines.next_back().unwrap(), "c");
+    assert_eq!(lines.next(), None);
+    assert_eq!(lines.next_back(), None);
+
+    let indexed_string = IndexedString::from_str("a\r\nb");
+    let mut lines = indexed_string.lines();
+    assert_eq!(lines.next_back().unwrap(), "b");
+    assert_eq!(lines.next().unwrap(), "a");
+    assert_eq!(lines.next(), None);
+}
+
+#[cfg(feature = "unicode")]
+#[test]
+fn test_is_identifier() {
+    for valid in ["foo", "foo_bar", "x1", "café", "変数", "Δx", "abc_123"] {
+        assert!(IndexedString::from_str(valid).is_identifier(), "{valid:?}");
+    }
+    for invalid in ["", "1foo", "foo-bar", "foo bar", "_foo", "😊", "a😊"] {
+        assert!(
+            !IndexedString::from_str(invalid).is_identifier(),
+            "{invalid:?}"
+        );
+    }
+    let indexed_string = IndexedString::from_str("1変数");
+    assert!(!indexed_string.is_identifier());
+    assert!(indexed_string.slice(1..).is_identifier());
+}
+
+#[test]
+fn test_is_ascii_identifier() {
+    for valid in ["foo", "foo_bar", "x1", "ABC_123"] {
+        assert!(
+            IndexedString::from_str(valid).is_ascii_identifier(),
+            "{valid:?}"
+        );
+    }
+    for invalid in ["", "1foo", "foo-bar", "_foo", "café", "変数"] {
+        assert!(
+            !IndexedString::from_str(invalid).is_ascii_identifier(),
+            "{invalid:?}"
+        );
+    }
+    assert!(IndexedString::from_str("9abc")
+        .slice(1..)
+        .is_ascii_identifier());
+}
+
+#[test]
+fn test_split_whitespace() {
+    let indexed_string = IndexedString::from_str("  héllo   世界\t\n😊 end\u{3000}ok  ");
+    let words: Vec<_> = indexed_string.split_whitespace().collect();
+    assert_eq!(words, vec!["héllo", "世界", "😊", "end", "ok"]);
+    for word in &words {
+        assert_eq!(indexed_string.slice(word.char_range()), *word);
+    }
+    assert_eq!(words[1].char_range(), 10..12);
+    assert_eq!(words[4].char_range(), 20..22);
+
+    assert_eq!(IndexedString::from_str("").split_whitespace().count(), 0);
+    assert_eq!(
+        IndexedString::from_str(" \u{3000}\t")
+            .split_whitespace()
+            .count(),
+        0
+    );
+    assert_eq!(
+        IndexedString::from_str("one")
+            .split_whitespace()
+            .collect::<Vec<_>>(),
+        vec!["one"]
+    );
+}
+
+#[test]
+fn test_split_whitespace_slice() {
+    let indexed_string = IndexedString::from_str("ab cd ef");
+    let slice = indexed_string.slice(1..7);
+    let words: Vec<_> = slice.split_whitespace().collect();
+    assert_eq!(words, vec!["b", "cd", "e"]);
+    assert_eq!(words[1].char_range(), 3..5);
+    assert_eq!(indexed_string.slice(words[2].char_range()), "e");
+}
+
+#[test]
+fn test_line_ranges() {
+    let document = IndexedString::from_str("fn 世界() {\n    😊\r\n\n}\n");
+    let ranges = document.line_ranges();
+    assert_eq!(
+        ranges,
+        vec![
+            (0, 0..9),
+            (1, 10..15),
+            (2, 17..17),
+            (3, 18..19),
+            (4, 20..20)
+        ]
+    );
+    for ((number, range), line) in ranges.iter().zip(document.lines()) {
+        assert_eq!(document.slice(range.clone()), line);
+        assert_eq!(document.line_index_of_char(range.start), Some(*number));
+    }
+
+    // The ranges tile the document, leaving gaps only for line terminators
+    let mut covered = 0;
+    for (_, range) in &ranges {
+        let gap = document.slice(covered..range.start);
+        assert!(gap.is_empty() || gap == "\n" || gap == "\r\n", "{gap:?}");
+        covered = range.end;
+    }
+    assert_eq!(covered, document.len());
+
+    let slice = document.slice(10..19);
+    assert_eq!(slice.line_ranges(), vec![(0, 0..5), (1, 7..7), (2, 8..9)]);
+    assert_eq!(IndexedString::from_str("").line_ranges(), vec![(0, 0..0)]);
+}
+
+#[test]
+fn test_repeat() {
+    let indexed_string = IndexedString::from_str("a世😊");
+    let repeated = indexed_string.repeat(3);
+    assert_eq!(repeated, "a世😊a世😊a世😊");
+    assert_eq!(repeated.len(), indexed_string.len() * 3);
+    assert_eq!(repeated.byte_len(), indexed_string.byte_len() * 3);
+    assert_eq!(repeated, IndexedString::from_str("a世😊a世😊a世😊"));
+    assert_eq!(repeated.char_at(7), Some('世'));
+    assert_eq!(repeated.slice(4..8), "世😊a世");
+
+    let slice = indexed_string.slice(1..);
+    let repeated = slice.repeat(2);
+    assert_eq!(repeated, "世😊世😊");
+    assert_eq!(repeated, IndexedString::from_str("世😊世😊"));
+
+    assert_eq!(indexed_string.repeat(0), "");
+    assert_eq!(indexed_string.repeat(0).len(), 0);
+    assert_eq!(IndexedString::from_str("").repeat(5).len(), 0);
+}
+
+#[test]
+fn test_char_containing_byte() {
+    // 'a' is byte 0, '世' is bytes 1..4, '😊' is bytes 4..8 and 'b' is byte 8
+    let indexed_string = IndexedString::from_str("a世😊b");
+    assert_eq!(indexed_string.char_containing_byte(0), Some((0, 'a')));
+    assert_eq!(indexed_string.char_containing_byte(1), Some((1, '世')));
+    assert_eq!(indexed_string.char_containing_byte(3), Some((1, '世')));
+    assert_eq!(indexed_string.char_containing_byte(4), Some((2, '😊')));
+    assert_eq!(indexed_string.char_containing_byte(6), Some((2, '😊')));
+    assert_eq!(indexed_string.char_containing_byte(8), Some((3, 'b')));
+    assert_eq!(indexed_string.char_containing_byte(9), Some((3, 'b')));
+    assert_eq!(indexed_string.char_containing_byte(100), Some((3, 'b')));
+
+    let slice = indexed_string.slice(1..3);
+    assert_eq!(slice.char_containing_byte(0), Some((0, '世')));
+    assert_eq!(slice.char_containing_byte(5), Some((1, '😊')));
+    assert_eq!(slice.char_containing_byte(7), Some((1, '😊')));
+    assert_eq!(slice.char_containing_byte(50), Some((1, '😊')));
+
+    assert_eq!(IndexedString::from_str("").char_containing_byte(0), None);
+    assert_eq!(indexed_string.slice(2..2).char_containing_byte(0), None);
+}
+
+#[test]
+fn test_find_with_char_predicate() {
+    let indexed_string = IndexedString::from_str("世界😊 item42");
+    assert_eq!(indexed_string.find(|c: char| c.is_numeric()), Some(8));
+    assert_eq!(indexed_string.find('😊'), Some(2));
+    assert_eq!(indexed_string.find("item"), Some(4));
+    assert_eq!(indexed_string.find(String::from("界😊")), Some(1));
+    assert_eq!(indexed_string.find(""), Some(0));
+    assert_eq!(indexed_string.find('x'), None);
+    assert_eq!(indexed_string.find(|c: char| c.is_uppercase()), None);
+
+    let slice = indexed_string.slice(2..9);
+    assert_eq!(slice.find(char::is_numeric), Some(6));
+    assert_eq!(slice.find("42"), None);
+    assert_eq!(IndexedString::from_str("").find(""), Some(0));
+}
+
+#[test]
+fn test_contains_and_split_with_char_predicate() {
+    let indexed_string = IndexedString::from_str("a1世2😊33b");
+    assert!(indexed_string.contains(|c: char| c.is_ascii_digit()));
+    assert!(indexed_string.contains('😊'));
+    assert!(!indexed_string.contains(char::is_whitespace));
+    assert!(!indexed_string
+        .slice(2..3)
+        .contains(|c: char| c.is_ascii_digit()));
+
+    let pieces: Vec<_> = indexed_string.split(|c: char| c.is_ascii_digit()).collect();
+    assert_eq!(pieces, vec!["a", "世", "😊", "", "b"]);
+    assert_eq!(
+        pieces,
+        "a1世2😊33b"
+            .split(|c: char| c.is_ascii_digit())
+            .collect::<Vec<_>>()
+    );
+
+    let pieces: Vec<_> = indexed_string.split("33").collect();
+    assert_eq!(pieces, vec!["a1世2😊", "b"]);
+    let slice = indexed_string.slice(1..6);
+    let pieces: Vec<_> = slice.split(char::is_alphabetic).collect();
+    assert_eq!(pieces, vec!["1", "2😊3"]);
+}
+
+#[test]
+fn test_swap() {
+    let mut indexed_string = IndexedString::from_str("ab世界");
+    indexed_string.swap(0, 1);
+    assert_eq!(indexed_string, "ba世界");
+    indexed_string.swap(3, 2);
+    assert_eq!(indexed_string, "ba界世");
+    assert_eq!(indexed_string.char_at(3), Some('世'));
+
+    indexed_string.swap(1, 1);
+    indexed_string.swap(0, 4);
+    indexed_string.swap(10, 0);
+    assert_eq!(indexed_string, "ba界世");
+}
+
+#[test]
+fn test_swap_different_lengths() {
+    let mut indexed_string = IndexedString::from_str("a世b😊c");
+    indexed_string.swap(0, 3);
+    assert_eq!(indexed_string, "😊世bac");
+    assert_eq!(indexed_string.len(), 5);
+    assert_eq!(indexed_string.char_at(0), Some('😊'));
+    assert_eq!(indexed_string.char_at(1), Some('世'));
+    assert_eq!(indexed_string.char_at(3), Some('a'));
+    assert_eq!(indexed_string.slice(1..4), "世ba");
+
+    indexed_string.swap(4, 1);
+    assert_eq!(indexed_string, "😊cba世");
+    assert_eq!(indexed_string.char_at(4), Some('世'));
+    assert_eq!(indexed_string.slice(2..), "ba世");
+}
+
+#[test]
+fn test_splitn() {
+    let indexed_string = IndexedString::from_str("key=välue=extra=😊");
+    let pieces: Vec<_> = indexed_string.splitn(2, '=').collect();
+    assert_eq!(pieces, vec!["key", "välue=extra=😊"]);
+    assert_eq!(indexed_string.splitn(0, '=').count(), 0);
+    assert_eq!(
+        indexed_string.splitn(1, '=').collect::<Vec<_>>(),
+        vec!["key=välue=extra=😊"]
+    );
+    assert_eq!(
+        indexed_string.splitn(10, '=').collect::<Vec<_>>(),
+        vec!["key", "välue", "extra", "😊"]
+    );
+
+    let indexed_string = IndexedString::from_str("a→→b→→c→→");
+    for n in 0..6 {
+        let pieces: Vec<_> = indexed_string.splitn(n, "→→").collect();
+        assert_eq!(pieces, "a→→b→→c→→".splitn(n, "→→").collect::<Vec<_>>());
+    }
+    for n in 0..6 {
+        let pieces: Vec<_> = indexed_string.splitn(n, "").collect();
+        assert_eq!(pieces, "a→→b→→c→→".splitn(n, "").collect::<Vec<_>>());
+    }
+}
+
+#[test]
+fn test_rsplitn() {
+    let indexed_string = IndexedString::from_str("😊=key=välue=extra");
+    let pieces: Vec<_> = indexed_string.rsplitn(2, '=').collect();
+    assert_eq!(pieces, vec!["extra", "😊=key=välue"]);
+    assert_eq!(pieces[1].char_range(), 0..11);
+    assert_eq!(indexed_string.rsplitn(0, '=').count(), 0);
+    assert_eq!(
+        indexed_string.rsplitn(1, '=').collect::<Vec<_>>(),
+        vec!["😊=key=välue=extra"]
+    );
+
+    let indexed_string = IndexedString::from_str("→→a→→b→→→c");
+    for n in 0..7 {
+        let pieces: Vec<_> = indexed_string.rsplitn(n, "→→").collect();
+        assert_eq!(pieces, "→→a→→b→→→c".rsplitn(n, "→→").collect::<Vec<_>>());
+        let pieces: Vec<_> = indexed_string.rsplitn(n, "").collect();
+        assert_eq!(pieces, "→→a→→b→→→c".rsplitn(n, "").collect::<Vec<_>>());
+        let pieces: Vec<_> = indexed_string.rsplitn(n, '→').collect();
+        assert_eq!(pieces, "→→a→→b→→→c".rsplitn(n, '→').collect::<Vec<_>>());
+    }
+
+    let slice = indexed_string.slice(2..8);
+    assert_eq!(slice, "a→→b→→");
+    assert_eq!(slice.rsplitn(2, "→→").collect::<Vec<_>>(), vec!["", "a→→b"]);
+}
+
+#[test]
+fn test_sorted_chars() {
+    let listen = IndexedString::from_str("lïsten😊世");
+    let silent = IndexedString::from_str("世😊sïlent");
+    assert_eq!(listen.sorted_chars(), silent.sorted_chars());
+    assert_eq!(listen.sorted_chars(), "elnstï世😊");
+    assert_ne!(
+        listen.sorted_chars(),
+        IndexedString::from_str("listen").sorted_chars()
+    );
+
+    let sorted = listen.slice(5..).sorted_chars();
+    assert_eq!(sorted, "n世😊");
+    assert_eq!(sorted.char_at(2), Some('😊'));
+    assert_eq!(IndexedString::from_str("").sorted_chars(), "");
+}
+
+#[test]
+fn test_sort_chars() {
+    let mut indexed_string = IndexedString::from_str("😊cb世a");
+    indexed_string.sort_chars();
+    assert_eq!(indexed_string, "abc世😊");
+    assert_eq!(indexed_string.len(), 5);
+    assert_eq!(indexed_string.char_at(3), Some('世'));
+    assert_eq!(indexed_string.slice(3..), "世😊");
+}
+
+#[test]
+fn test_is_anagram_of() {
+    let a = IndexedString::from_str("😊🎉世a");
+    let b = IndexedString::from_str("a世🎉😊");
+    assert!(a.is_anagram_of(&b));
+    assert!(b.is_anagram_of(&a));
+    assert!(a.is_anagram_of(&a.slice(..)));
+    assert!(IndexedString::from_str("listen").is_anagram_of(&IndexedString::from_str("silent")));
+
+    assert!(!a.is_anagram_of(&IndexedString::from_str("a世🎉😊😊")));
+    assert!(!a.is_anagram_of(&IndexedString::from_str("a世🎉")));
+    assert!(!a.is_anagram_of(&IndexedString::from_str("a世🎉🎉")));
+
+    // Combining marks are counted as characters of their own
+    let x = IndexedString::from_str("\u{e9}\u{301}");
+    assert!(x.is_anagram_of(&IndexedString::from_str("\u{301}\u{e9}")));
+    assert!(!x.is_anagram_of(&IndexedString::from_str("e\u{301}")));
+
+    assert!(IndexedString::from_str("").is_anagram_of(&IndexedString::from_str("")));
+
+    // ASCII and non-ASCII counts must both balance
+    let mixed = IndexedString::from_str("ab\u{7f}é世");
+    assert!(mixed.is_anagram_of(&IndexedString::from_str("世\u{7f}éba")));
+    assert!(!mixed.is_anagram_of(&IndexedString::from_str("世\u{7f}eba")));
+    assert!(!mixed.is_anagram_of(&IndexedString::from_str("世\u{7f}éb\u{80}")));
+}
+
+#[test]
+fn test_match_indices() {
+    let indexed_string = IndexedString::from_str("aaaa");
+    let matches: Vec<_> = indexed_string.match_indices("aa").collect();
+    assert_eq!(matches.len(), 2);
+    assert_eq!(matches[0].0, 0);
+    assert_eq!(matches[1].0, 2);
+    assert_eq!(matches[1].1, "aa");
+    assert_eq!(matches[1].1.char_range(), 2..4);
+
+    let indexed_string = IndexedString::from_str("世界😊世界x世界");
+    let indices: Vec<_> = indexed_string
+        .match_indices("世界")
+        .map(|(i, m)| (i, m.char_range()))
+        .collect();
+    assert_eq!(indices, vec![(0, 0..2), (3, 3..5), (6, 6..8)]);
+    assert_eq!(indexed_string.match_indices("?").count(), 0);
+
+    let indices: Vec<_> = IndexedString::from_str("a😊b")
+        .match_indices("")
+        .map(|(i, m)| (i, m.len()))
+        .collect();
+    assert_eq!(indices, vec![(0, 0), (1, 0), (2, 0), (3, 0)]);
+}
+
+#[test]
+fn test_match_indices_in_slice() {
+    let indexed_string = IndexedString::from_str("世界😊世界x世界");
+    let slice = indexed_string.slice(1..7);
+    assert_eq!(slice, "界😊世界x世");
+    let matches: Vec<_> = slice.match_indices("世界").collect();
+    assert_eq!(matches.len(), 1);
+    assert_eq!(matches[0].0, 2);
+    assert_eq!(matches[0].1, "世界");
+    assert_eq!(slice.slice(matches[0].0..matches[0].0 + 2), "世界");
+
+    let indices: Vec<_> = slice.match_indices("").map(|(i, _)| i).collect();
+    assert_eq!(indices, (0..=6).collect::<Vec<_>>());
+}
+
+#[test]
+fn test_find_all_words() {
+    let indexed_string = IndexedString::from_str("cat concatenate cat_s (cat) 猫cat cat");
+    assert_eq!(indexed_string.find_all_words("cat"), vec![0, 23, 33]);
+    assert_eq!(indexed_string.find_all_words("concatenate"), vec![4]);
+    assert!(indexed_string.find_all_words("cate").is_empty());
+    assert!(indexed_string.find_all_words("").is_empty());
+
+    let indexed_string = IndexedString::from_str("世界, 世界人, 😊世界😊");
+    assert_eq!(indexed_string.find_all_words("世界"), vec![0, 10]);
+
+    // Boundaries are judged within the slice, not the source
+    let indexed_string = IndexedString::from_str("xcat cat");
+    let slice = indexed_string.slice(1..);
+    assert_eq!(slice.find_all_words("cat"), vec![0, 4]);
+
+    let indexed_string = IndexedString::from_str("cat catx");
+    let slice = indexed_string.slice(0..7);
+    assert_eq!(slice.find_all_words("cat"), vec![0, 4]);
+    assert_eq!(indexed_string.find_all_words("cat"), vec![0]);
+}
+
+#[test]
+fn test_reversed() {
+    // 1-, 2-, 3- and 4-byte characters
+    let indexed_string = IndexedString::from_str("aé世😊");
+    let reversed = indexed_string.reversed();
+    assert_eq!(reversed, "😊世éa");
+    assert_eq!(reversed.len(), indexed_string.len());
+    assert_eq!(reversed.byte_len(), indexed_string.byte_len());
+    assert_eq!(reversed.char_at(0), Some('😊'));
+    assert_eq!(reversed.char_at(2), Some('é'));
+    assert_eq!(reversed.slice(1..3), "世é");
+    assert_eq!(reversed.reversed(), indexed_string);
+
+    assert_eq!(IndexedString::from_str("a😊b").reversed(), "b😊a");
+    assert_eq!(indexed_string.slice(1..3).reversed(), "世é");
+    assert_eq!(IndexedString::from_str("").reversed(), "");
+}
+
+#[test]
+fn test_reversed_detaches_combining_marks() {
+    let indexed_string = IndexedString::from_str("e\u{301}x");
+    let reversed = indexed_string.reversed();
+    assert_eq!(reversed, "x\u{301}e");
+    assert_eq!(reversed.char_at(1), Some('\u{301}'));
+}
+
+#[test]
+fn test_char_ngrams() {
+    let indexed_string = IndexedString::from_str("a世😊bé");
+    let trigrams = indexed_string.char_ngrams(3);
+    assert_eq!(trigrams, vec!["a世😊", "世😊b", "😊bé"]);
+    assert_eq!(trigrams[1].char_range(), 1..4);
+    assert_eq!(indexed_string.char_ngrams(5), vec!["a世😊bé"]);
+    assert_eq!(indexed_string.char_ngrams(1).len(), 5);
+    assert!(indexed_string.char_ngrams(0).is_empty());
+    assert!(indexed_string.char_ngrams(6).is_empty());
+
+    let slice = indexed_string.slice(1..4);
+    assert_eq!(slice.char_ngrams(2), vec!["世😊", "😊b"]);
+}
+
+#[cfg(feature = "unicode")]
+#[test]
+#[allow(clippy::reversed_empty_ranges)]
+fn test_indexed_grapheme_string() {
+    // Waving hand + skin tone modifier, 'e' + combining acute accent, and a family ZWJ sequence
+    let text = "a👋🏽e\u{301}👨\u{200d}👩\u{200d}👧b";
+    let indexed_string = IndexedString::from_str(text);
+    let graphemes = IndexedGraphemeString::from_str(text);
+    assert_eq!(indexed_string.len(), 11);
+    assert_eq!(graphemes.len(), 5);
+    assert_eq!(graphemes.byte_len(), text.len());
+    assert_eq!(graphemes, text);
+
+    assert_eq!(graphemes.grapheme_at(0), Some("a"));
+    assert_eq!(graphemes.grapheme_at(1), Some("👋🏽"));
+    assert_eq!(graphemes.grapheme_at(2), Some("e\u{301}"));
+    assert_eq!(graphemes.grapheme_at(3), Some("👨\u{200d}👩\u{200d}👧"));
+    assert_eq!(graphemes.grapheme_at(4), Some("b"));
+    assert_eq!(graphemes.grapheme_at(5), None);
+
+    assert_eq!(graphemes.slice(1..3), "👋🏽e\u{301}");
+    assert_eq!(graphemes.slice(3..100), "👨\u{200d}👩\u{200d}👧b");
+    assert_eq!(graphemes.slice(4..2), "");
+    assert_eq!(graphemes.slice(..), text);
+
+    // No slice boundary ever lands inside a cluster
+    for start in 0..=graphemes.len() {
+        for end in start..=graphemes.len() {
+            let slice = graphemes.slice(start..end);
+            assert_eq!(
+                IndexedGraphemeString::from_str(slice).len(),
+                end - start,
+                "{slice:?}"
+            );
+        }
+    }
+    assert_eq!(graphemes.graphemes().count(), 5);
+    assert!(IndexedGraphemeString::from_str("").is_empty());
+}
+
+#[test]
+fn test_jaccard_ngram_similarity() {
+    let base = IndexedString::from_str("世界😊ab");
+    assert_eq!(base.jaccard_ngram_similarity(&base, 2), 1.0);
+    assert_eq!(
+        base.jaccard_ngram_similarity(&IndexedString::from_str("xyzw"), 2),
+        0.0
+    );
+
+    // Bigrams: {世界, 界😊, 😊a, ab} vs {世界, 界😊, 😊x} share 2 of 5
+    let close = IndexedString::from_str("世界😊x");
+    assert_eq!(base.jaccard_ngram_similarity(&close, 2), 0.4);
+
+    // More shared n-grams yield a higher score
+    let far = IndexedString::from_str("世界xyz");
+    let scores: Vec<f64> = [&far, &close, &base]
+        .iter()
+        .map(|other| base.jaccard_ngram_similarity(*other, 2))
+        .collect();
+    assert!(scores[0] < scores[1] && scores[1] < scores[2]);
+
+    assert_eq!(
+        base.slice(..3)
+            .jaccard_ngram_similarity(&close.slice(..3), 2),
+        1.0
+    );
+
+    let short = IndexedString::from_str("a");
+    assert_eq!(short.jaccard_ngram_similarity(&short, 3), 1.0);
+    assert_eq!(
+        short.jaccard_ngram_similarity(&IndexedString::from_str("b"), 3),
+        0.0
+    );
+}
+
+#[test]
+fn test_char_iter() {
+    let indexed_string = IndexedString::from_str("xa世😊éy");
+    let slice = indexed_string.slice(1..5);
+    assert!(slice.char_iter().eq(['a', '世', '😊', 'é']));
+    assert!(slice.char_iter().rev().eq(['é', '😊', '世', 'a']));
+    assert_eq!(slice.char_iter().count(), slice.len());
+    assert_eq!(slice.char_iter().len(), 4);
+    assert_eq!(slice.char_iter().rev().enumerate().nth(1), Some((1, '😊')));
+
+    let mut chars = slice.char_iter();
+    assert_eq!(chars.next(), Some('a'));
+    assert_eq!(chars.next_back(), Some('é'));
+    assert_eq!(chars.len(), 2);
+    assert_eq!(chars.next_back(), Some('😊'));
+    assert_eq!(chars.next(), Some('世'));
+    assert_eq!(chars.next(), None);
+    assert_eq!(chars.next_back(), None);
+
+    assert_eq!(indexed_string.char_iter().count(), indexed_string.len());
+    assert_eq!(IndexedString::from_str("").char_iter().next_back(), None);
+}
+
+#[test]
+#[allow(clippy::reversed_empty_ranges)]
+fn test_index_range() {
+    let indexed_string = IndexedString::from_str("a世😊bé");
+    assert_eq!(&indexed_string[1..4], "世😊b");
+    for start in 0..8 {
+        for end in 0..8 {
+            assert_eq!(
+                &indexed_string[start..end],
+                indexed_string.slice(start..end).as_str()
+            );
+        }
+    }
+    assert_eq!(&indexed_string[3..100], "bé");
+    assert_eq!(&indexed_string[10..20], "");
+    assert_eq!(&indexed_string[4..2], "");
+
+    let slice = indexed_string.slice(1..4);
+    assert_eq!(&slice[1..2], "😊");
+    assert_eq!(&slice[1..10], "😊b");
+    assert_eq!(&slice[5..6], "");
+}
+
+#[test]
+fn test_index_range_owned_slice() {
+    use std::sync::Arc;
+
+    let owned = Arc::new(IndexedString::from_str("a世界b")).arc_slice(..);
+    assert_eq!(&owned[1..3], "世界");
+    assert_eq!(&owned[0..4], "a世界b");
+    assert_eq!(&owned[2..100], "界b");
+    assert_eq!(&owned[7..9], "");
+
+    let owned = Arc::new(IndexedString::from_str("x😊a世界b")).arc_slice(1..5);
+    assert_eq!(&owned[1..3], "a世");
+    assert_eq!(&owned[3..10], "界");
+}
+
+#[test]
+fn test_arc_slice_outlives_binding() {
+    use std::sync::Arc;
+
+    fn greeting() -> OwnedIndexedSlice {
+        let indexed_string = Arc::new(IndexedString::from_str("¡Hola, 世界! 😊"));
+        indexed_string.arc_slice(7..)
+    }
+
+    let owned = greeting();
+    assert_eq!(owned, "世界! 😊");
+    assert_eq!(owned.len(), 5);
+    assert_eq!(owned.char_at(0), Some('世'));
+    assert_eq!(owned.char_at(4), Some('😊'));
+    assert_eq!(owned.char_at(5), None);
+    assert_eq!(owned.slice(1..3), "界!");
+    assert_eq!(owned.byte_len(), "世界! 😊".len());
+    assert_eq!(owned.to_indexed_string(), "世界! 😊");
+
+    let indexed_string = Arc::new(IndexedString::from_str("a\nb😊\nc"));
+    let owned = indexed_string.clone().arc_slice(2..100);
+    drop(indexed_string);
+    let shared = owned.clone();
+    assert_eq!(shared.lines().collect::<Vec<_>>(), vec!["b😊", "c"]);
+    assert!(shared.char_iter().rev().eq(['c', '\n', '😊', 'b']));
+}
+
+#[test]
+fn test_into_owned_slice() {
+    fn first_word(text: &str) -> OwnedIndexedSlice {
+        let indexed_string = IndexedString::from_str(text);
+        let word = indexed_string.split_whitespace().next().unwrap();
+        word.into_owned_slice()
+    }
+
+    let owned = first_word("  héllo 世界");
+    assert_eq!(owned, "héllo");
+    assert_eq!(owned.len(), 5);
+    assert_eq!(owned.char_at(1), Some('é'));
+    assert_eq!(owned.find('l'), Some(2));
+    assert_eq!(format!("{owned}"), "héllo");
+}
+
+#[test]
+fn test_contains_and_find_with_string_types() {
+    use std::borrow::Cow;
+    use std::sync::Arc;
+
+    let haystack = IndexedString::from_str("a😊b世界c");
+    let needle_source = IndexedString::from_str("x世界y");
+    let needle = needle_source.slice(1..3);
+    assert!(haystack.contains(needle.clone()));
+    assert!(haystack.contains(&needle));
+    assert_eq!(haystack.find(&needle), Some(3));
+    assert!(!haystack.contains(needle_source.slice(0..2)));
+
+    let owned = Arc::new(needle_source.clone()).arc_slice(1..3);
+    assert!(haystack.contains(owned.clone()));
+    assert_eq!(haystack.slice(2..).find(owned), Some(1));
+
+    assert!(haystack.contains(Cow::Borrowed("b世")));
+    assert!(haystack.contains(Box::<str>::from("界c")));
+    assert_eq!(haystack.find(Cow::Owned(String::from("c"))), Some(5));
+    assert_eq!(haystack.find(""), Some(0));
+    assert_eq!(haystack.slice(6..).find(""), Some(0));
+    assert_eq!(haystack.slice(1..4).find("世界"), None);
+}